@@ -69,4 +69,32 @@ impl button {
     pub const NAME_SPACE: Option<&'static str> = None;
 }
 
-impl GlobalAttributes for button {}
\ No newline at end of file
+impl GlobalAttributes for button {}
+
+pub struct entity;
+impl entity {
+    pub const TAG_NAME: &'static str = "entity";
+    pub const NAME_SPACE: Option<&'static str> = None;
+}
+
+// Entity that gets placed in the tree, managed outside of Dioxus
+#[allow(non_upper_case_globals)]
+impl entity {
+    pub const entity: (&'static str, Option<&'static str>, bool) = ("entity", None, false);
+}
+
+impl GlobalAttributes for entity {}
+
+pub struct portal;
+impl portal {
+    pub const TAG_NAME: &'static str = "portal";
+    pub const NAME_SPACE: Option<&'static str> = None;
+}
+
+// Entity children get spawned under, they're top level UI nodes without it
+#[allow(non_upper_case_globals)]
+impl portal {
+    pub const target: (&'static str, Option<&'static str>, bool) = ("target", None, false);
+}
+
+impl GlobalAttributes for portal {}
\ No newline at end of file
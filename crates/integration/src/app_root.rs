@@ -1,9 +1,110 @@
-use bevy::prelude::{Resource, Component};
-use dioxus::prelude::{Scope, Element};
+use std::{sync::Arc, rc::Rc, cell::RefCell, any::{Any, type_name}};
 
-// TODO: Maybe make plugin setting instead of resource
-#[derive(Resource)]
-pub struct AppRootComponent(pub fn(Scope) -> Element);
+use bevy::{prelude::{Component, Entity, World, DespawnRecursiveExt}, log::warn, ecs::system::Command};
+use dioxus::{prelude::{Scope, Element, VirtualDom, Properties, IntoDynNode, rsx}, core::ScopeId};
 
+use crate::integration::unmount_root;
+
+// Marks the root spawned from DioxusPlugin's root component
 #[derive(Component)]
-pub struct AppRootElement;
\ No newline at end of file
+pub struct AppRootElement;
+
+// Mounts a VirtualDom under the entity it's added to, removing it or
+// despawning the entity unmounts it. For UI scoped to a Bevy State, spawn
+// it in OnEnter and despawn it with DespawnDioxusRoot in OnExit, plain
+// despawn_recursive takes entities placed with the entity element with it
+#[derive(Component, Clone)]
+pub struct DioxusRoot {
+    // VirtualDom isn't Send, so components only carry what's needed to build it
+    root: Arc<dyn RootComponent>,
+}
+
+impl DioxusRoot {
+    pub fn new(root: fn(Scope) -> Element) -> Self {
+        Self::with_props(root, ())
+    }
+    pub fn with_props<P: Properties + Clone + Send + Sync + 'static>(
+        root: fn(Scope<P>) -> Element,
+        props: P,
+    ) -> Self {
+        Self {
+            root: Arc::new(Root { root, props }),
+        }
+    }
+    // Mounted root re-renders with the new props, keeping its state.
+    // Returns false and keeps the old props when P isn't the root's props type
+    pub fn set_props<P: Properties + Clone + Send + Sync + 'static>(&mut self, props: P) -> bool {
+        let Some(root) = self.root.as_any().downcast_ref::<Root<P>>() else {
+            warn!("ignored props of type {}, they don't match the root component's", type_name::<P>());
+            return false;
+        };
+
+        self.root = Arc::new(Root { root: root.root, props });
+        true
+    }
+    pub fn create_vdom(&self) -> VirtualDom {
+        self.root.create_vdom()
+    }
+    pub fn update_vdom(&self, vdom: &mut VirtualDom) {
+        self.root.update_vdom(vdom);
+    }
+}
+
+// Unmounts the root right away, detaching entities placed with the
+// entity element, and then despawns the root with its children
+pub struct DespawnDioxusRoot {
+    pub entity: Entity,
+}
+
+impl Command for DespawnDioxusRoot {
+    fn apply(self, world: &mut World) {
+        unmount_root(world, self.entity);
+
+        if let Some(entity) = world.get_entity_mut(self.entity) {
+            entity.despawn_recursive();
+        }
+    }
+}
+
+trait RootComponent: Send + Sync {
+    fn create_vdom(&self) -> VirtualDom;
+    fn update_vdom(&self, vdom: &mut VirtualDom);
+    fn as_any(&self) -> &dyn Any;
+}
+
+struct Root<P> {
+    root: fn(Scope<P>) -> Element,
+    props: P,
+}
+
+// Root props can't be replaced in a VirtualDom, so the vdom's root is a wrapper
+// rendering the actual root with props from a cell shared through context
+type RootPropsCell<P> = Rc<RefCell<P>>;
+
+struct RootWrapperProps<P> {
+    root: fn(Scope<P>) -> Element,
+}
+
+impl<P: Properties + Clone + Send + Sync + 'static> RootComponent for Root<P> {
+    fn create_vdom(&self) -> VirtualDom {
+        let vdom = VirtualDom::new_with_props(root_wrapper::<P>, RootWrapperProps { root: self.root });
+        vdom.base_scope().provide_context::<RootPropsCell<P>>(Rc::new(RefCell::new(self.props.clone())));
+        vdom
+    }
+    fn update_vdom(&self, vdom: &mut VirtualDom) {
+        let props = vdom.base_scope().consume_context::<RootPropsCell<P>>().unwrap();
+        *props.borrow_mut() = self.props.clone();
+        vdom.mark_dirty(ScopeId::ROOT);
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+fn root_wrapper<P: Properties + Clone + 'static>(cx: Scope<RootWrapperProps<P>>) -> Element {
+    let props = cx.use_hook(|| cx.consume_context::<RootPropsCell<P>>().unwrap());
+
+    // Memoized like any other component, so the root only re-renders if props changed
+    let root = cx.component(cx.props.root, props.borrow().clone(), "root");
+    cx.render(rsx! { root })
+}
@@ -1,13 +1,14 @@
-use bevy::{prelude::{NodeBundle, default, TextBundle, ImageBundle, ButtonBundle}, text::Text};
+use bevy::{prelude::{NodeBundle, default, TextBundle, ImageBundle, ButtonBundle, Component, Entity}, text::Text, ui::{Style, Display}, utils::HashSet};
 
 #[derive(Debug, Clone, Default)]
 pub struct SimpleNode {
-
+    pub style: Style,
 }
 
 impl SimpleNode {
     pub fn bundle(&self) -> NodeBundle {
         NodeBundle {
+            style: self.style.clone(),
             ..default()
         }
     }
@@ -29,12 +30,13 @@ impl TextNode {
 
 #[derive(Debug, Clone, Default)]
 pub struct ImageNode {
-
+    pub style: Style,
 }
 
 impl ImageNode {
     pub fn bundle(&self) -> ImageBundle {
         ImageBundle {
+            style: self.style.clone(),
             ..default()
         }
     }
@@ -42,13 +44,85 @@ impl ImageNode {
 
 #[derive(Debug, Clone, Default)]
 pub struct ButtonNode {
-
+    pub style: Style,
 }
 
 impl ButtonNode {
     pub fn bundle(&self) -> ButtonBundle {
         ButtonBundle {
+            style: self.style.clone(),
             ..default()
         }
     }
-}
\ No newline at end of file
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct EntityNode {
+    pub style: Style,
+}
+
+impl EntityNode {
+    pub fn bundle(&self) -> (NodeBundle, EntityProxy) {
+        (
+            NodeBundle {
+                style: self.style.clone(),
+                ..default()
+            },
+            EntityProxy::default(),
+        )
+    }
+}
+
+// Holds an externally managed entity as its child, which gets
+// detached instead of despawned with the proxy
+#[derive(Component, Debug, Default)]
+pub struct EntityProxy {
+    pub entity: Option<Entity>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PortalNode {
+    pub style: Style,
+}
+
+impl PortalNode {
+    pub fn bundle(&self) -> (NodeBundle, Portal) {
+        (
+            // Only keeps the portal's place in the tree
+            NodeBundle {
+                style: Style {
+                    display: Display::None,
+                    ..default()
+                },
+                ..default()
+            },
+            Portal {
+                content: None,
+                content_style: self.style.clone(),
+            },
+        )
+    }
+}
+
+// Children and styles of a portal go to its content node, which
+// gets spawned on first use and parented to the portal's target
+#[derive(Component, Debug, Default)]
+pub struct Portal {
+    pub content: Option<Entity>,
+    // Static styles from the template, for when content gets spawned
+    pub content_style: Style,
+}
+
+// Points back to the portal, content isn't its descendant
+// so it has to be found this way once the portal is gone
+#[derive(Component, Debug)]
+pub struct PortalContent {
+    pub portal: Entity,
+}
+
+// Names of the Dioxus events listened to on a node, there's
+// nothing dispatching them yet but the tree stays in sync
+#[derive(Component, Debug, Default)]
+pub struct EventListeners {
+    pub names: HashSet<String>,
+}
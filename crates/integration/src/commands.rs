@@ -0,0 +1,75 @@
+use std::{cell::RefCell, rc::Rc, marker::PhantomData};
+
+use bevy::{prelude::{World, Entity, Bundle, Resource, Event, IntoSystem, System}, ecs::system::{CommandQueue, Command, Spawn, Despawn, Insert, Remove, InsertResource, RemoveResource}, hierarchy::DespawnRecursive};
+
+// BevyWorld is only bound while rendering, so handlers can't reach it and
+// mutations get queued to be applied by update_dioxus instead
+pub type BevyCommandQueue = Rc<RefCell<CommandQueue>>;
+
+#[derive(Clone)]
+pub struct UseCommands {
+    queue: BevyCommandQueue,
+}
+
+impl UseCommands {
+    pub fn new(queue: BevyCommandQueue) -> Self {
+        Self { queue }
+    }
+    pub fn add(&self, command: impl Command) {
+        self.queue.borrow_mut().push(command);
+    }
+    pub fn spawn(&self, bundle: impl Bundle) {
+        self.add(Spawn { bundle });
+    }
+    pub fn despawn(&self, entity: Entity) {
+        self.add(Despawn { entity });
+    }
+    pub fn despawn_recursive(&self, entity: Entity) {
+        self.add(DespawnRecursive { entity });
+    }
+    pub fn run_system<M>(&self, system: impl IntoSystem<(), (), M> + Send + 'static) {
+        // Initialized on every run, so keep these for rarely ran logic
+        self.add(move |world: &mut World| {
+            let mut system = IntoSystem::into_system(system);
+            system.initialize(world);
+            system.run((), world);
+            system.apply_deferred(world);
+        });
+    }
+    pub fn send_event<E: Event>(&self, event: E) {
+        self.add(move |world: &mut World| world.send_event(event));
+    }
+    pub fn insert(&self, entity: Entity, bundle: impl Bundle) {
+        self.add(Insert { entity, bundle });
+    }
+    pub fn remove<T: Bundle>(&self, entity: Entity) {
+        self.add(Remove::<T>::new(entity));
+    }
+    pub fn insert_resource<R: Resource>(&self, resource: R) {
+        self.add(InsertResource { resource });
+    }
+    pub fn remove_resource<R: Resource>(&self) {
+        self.add(RemoveResource::<R>::new());
+    }
+}
+
+pub struct UseEventWriter<E> {
+    commands: UseCommands,
+    _marker: PhantomData<E>,
+}
+
+impl<E: Event> UseEventWriter<E> {
+    pub fn new(commands: UseCommands) -> Self {
+        Self {
+            commands,
+            _marker: PhantomData,
+        }
+    }
+    pub fn send(&self, event: E) {
+        self.commands.send_event(event);
+    }
+    pub fn send_batch(&self, events: impl IntoIterator<Item = E>) {
+        let events: Vec<E> = events.into_iter().collect();
+        self.commands.add(move |world: &mut World| world.send_event_batch(events));
+    }
+}
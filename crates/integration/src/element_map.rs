@@ -1,9 +1,14 @@
-use bevy::prelude::Entity;
+use bevy::{prelude::Entity, utils::HashMap};
 use dioxus::core::ElementId;
 
+use crate::error::IntegrationError;
+
+// Dioxus reuses ids of removed elements, so freed ids are set back to
+// PLACEHOLDER and looking them up is an error until they're assigned again
 #[derive(Default, Debug)]
 pub struct ElementMap {
     entities: Vec<Entity>,
+    ids: HashMap<Entity, ElementId>,
 }
 
 impl ElementMap {
@@ -16,9 +21,35 @@ impl ElementMap {
             }
         }
 
-        self.entities[id.0] = entity;
+        let previous = std::mem::replace(&mut self.entities[id.0], entity);
+        if self.ids.get(&previous) == Some(&id) {
+            self.ids.remove(&previous);
+        }
+
+        self.ids.insert(entity, id);
+    }
+    pub fn get(&self, id: ElementId) -> Result<Entity, IntegrationError> {
+        match self.entities.get(id.0) {
+            Some(&entity) if entity != Entity::PLACEHOLDER => Ok(entity),
+            _ => Err(IntegrationError::UnknownElement(id)),
+        }
+    }
+    pub fn remove_entity(&mut self, entity: Entity) {
+        if let Some(id) = self.ids.remove(&entity) {
+            self.entities[id.0] = Entity::PLACEHOLDER;
+        }
+    }
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.ids.contains_key(&entity)
+    }
+}
+
+#[cfg(test)]
+impl ElementMap {
+    pub fn id(&self, entity: Entity) -> Option<ElementId> {
+        self.ids.get(&entity).copied()
     }
-    pub fn get(&self, id: ElementId) -> Entity {
-        self.entities[id.0]
+    pub fn len(&self) -> usize {
+        self.entities.len()
     }
 }
\ No newline at end of file
@@ -0,0 +1,42 @@
+use bevy::prelude::{Entity, Event};
+use dioxus::core::ElementId;
+use thiserror::Error;
+
+// Mutations that can't be applied are reported instead of panicking,
+// a bug in the UI shouldn't take the whole app down with it
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum IntegrationError {
+    #[error("no entity is mapped to {0:?}")]
+    UnknownElement(ElementId),
+    #[error("template {name} doesn't have a root at {index}")]
+    UnknownTemplate { name: String, index: usize },
+    #[error("template {0} closes more elements than it opens")]
+    MalformedTemplate(String),
+    #[error("invalid tag {0}")]
+    UnknownTag(String),
+    #[error("invalid attribute name {0}")]
+    UnknownAttribute(String),
+    #[error("invalid value for attribute {0}")]
+    InvalidAttributeValue(String),
+    #[error("invalid static value {value:?} for attribute {name}")]
+    InvalidStaticAttribute { name: String, value: String },
+    #[error("attribute {name} isn't supported on {entity:?}")]
+    UnsupportedAttribute { name: String, entity: Entity },
+    #[error("stack has less than {0} nodes")]
+    StackUnderflow(usize),
+    #[error("entity {0:?} doesn't exist")]
+    MissingEntity(Entity),
+    #[error("entity {0:?} doesn't have a parent")]
+    MissingParent(Entity),
+    #[error("entity {parent:?} doesn't have a child at {index}")]
+    MissingChild { parent: Entity, index: usize },
+    #[error("entity {child:?} isn't a child of {parent:?}")]
+    NotAChild { parent: Entity, child: Entity },
+}
+
+// Sent along with the logged error, root is the DioxusRoot's entity
+#[derive(Event, Debug, Clone)]
+pub struct DioxusError {
+    pub root: Entity,
+    pub error: IntegrationError,
+}
@@ -1,7 +1,9 @@
-use bevy::{ecs::query::WorldQuery, prelude::QueryState};
+use std::{rc::Rc, cell::{Cell, RefCell}, time::Duration};
+
+use bevy::{asset::{Asset, AssetServer, Handle, LoadState}, ecs::{query::WorldQuery, event::ManualEventReader, system::{BoxedSystem, SystemParam, SystemState, SystemParamItem}}, prelude::{QueryState, World, Time, DetectChangesMut, Resource, Component, Entity, Event, Events, IntoSystem}};
 use dioxus::prelude::ScopeState;
 
-use crate::integration::BevyWorld;
+use crate::{world_access::BevyWorld, commands::{BevyCommandQueue, UseCommands, UseEventWriter}, subscriptions::{subscribe, resource_change_tick, component_change_tick}};
 
 pub fn use_world(cx: &ScopeState) -> &mut BevyWorld {
     cx.use_hook(|| {
@@ -13,4 +15,320 @@ pub fn use_query<'a, Q: WorldQuery>(
     world: &mut BevyWorld,
 ) -> QueryState<Q> {
     world.borrow_mut().query::<Q>()
-}
\ No newline at end of file
+}
+
+pub fn use_resource<R: Resource + Clone>(cx: &ScopeState) -> R {
+    let world = use_world(cx);
+    use_resource_subscription::<R>(cx, world);
+
+    world.borrow().resource::<R>().clone()
+}
+
+pub fn use_resource_mut<R: Resource + Clone>(cx: &ScopeState) -> &UseResourceMut<R> {
+    let world = use_world(cx);
+    use_resource_subscription::<R>(cx, world);
+    let commands = use_commands(cx).clone();
+
+    let value = world.borrow().resource::<R>().clone();
+
+    let resource = cx.use_hook(|| UseResourceMut {
+        value: value.clone(),
+        commands,
+    });
+    resource.value = value;
+    resource
+}
+
+fn use_resource_subscription<R: Resource>(cx: &ScopeState, world: &BevyWorld) {
+    cx.use_hook(|| {
+        let mut last_changed = resource_change_tick::<R>(&world.borrow());
+
+        subscribe(cx, move |world| {
+            let changed = resource_change_tick::<R>(world);
+            let is_changed = changed != last_changed;
+            last_changed = changed;
+            is_changed
+        })
+    });
+}
+
+pub fn use_component<T: Component + Clone>(cx: &ScopeState, entity: Entity) -> Option<T> {
+    let world = use_world(cx);
+    use_component_subscription::<T>(cx, world, entity);
+
+    world.borrow().get::<T>(entity).cloned()
+}
+
+pub fn use_bound_component<T: Component, F: Clone + Send + 'static>(
+    cx: &ScopeState,
+    entity: Entity,
+    lens: fn(&mut T) -> &mut F,
+) -> &UseBoundComponent<T, F> {
+    let world = use_world(cx);
+    use_component_subscription::<T>(cx, world, entity);
+    let commands = use_commands(cx).clone();
+
+    // Reading through lens needs &mut, which shouldn't count as a change
+    let value = world
+        .borrow_mut()
+        .get_mut::<T>(entity)
+        .map(|mut component| lens(component.bypass_change_detection()).clone());
+
+    let bound = cx.use_hook(|| UseBoundComponent {
+        entity,
+        lens,
+        value: None,
+        commands,
+    });
+    bound.entity = entity;
+    bound.value = value;
+    bound
+}
+
+fn use_component_subscription<T: Component>(cx: &ScopeState, world: &BevyWorld, entity: Entity) {
+    // Entity is shared with the check, so the same hook can follow a new entity
+    // if the scope gets rendered with a different one
+    let (current, _) = cx.use_hook(|| {
+        let current = Rc::new(Cell::new(entity));
+        let mut last_changed = component_change_tick::<T>(&world.borrow(), entity);
+
+        let check_current = current.clone();
+        let subscription = subscribe(cx, move |world| {
+            let changed = component_change_tick::<T>(world, check_current.get());
+            let is_changed = changed != last_changed;
+            last_changed = changed;
+            is_changed
+        });
+
+        (current, subscription)
+    });
+    current.set(entity);
+}
+
+pub fn use_event_reader<E: Event + Clone>(cx: &ScopeState) -> Vec<E> {
+    let world = use_world(cx);
+
+    let reader = cx.use_hook(|| {
+        let reader = Rc::new(RefCell::new(ManualEventReader::<E>::default()));
+
+        // Only peeks, events are consumed when the scope renders
+        let check_reader = reader.clone();
+        let subscription = subscribe(cx, move |world| {
+            world
+                .get_resource::<Events<E>>()
+                .is_some_and(|events| !check_reader.borrow().is_empty(events))
+        });
+
+        (reader, subscription)
+    }).0.clone();
+
+    // Events that were never registered read as empty
+    let world = world.borrow();
+    let Some(events) = world.get_resource::<Events<E>>() else {
+        return Vec::new();
+    };
+    let received = reader.borrow_mut().iter(events).cloned().collect();
+    received
+}
+
+pub fn use_commands(cx: &ScopeState) -> &UseCommands {
+    cx.use_hook(|| {
+        UseCommands::new(cx.consume_context::<BevyCommandQueue>().unwrap())
+    })
+}
+
+pub fn use_event_writer<E: Event>(cx: &ScopeState) -> &UseEventWriter<E> {
+    let commands = use_commands(cx).clone();
+    cx.use_hook(|| UseEventWriter::new(commands))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AssetState<A: Asset> {
+    Loading,
+    Loaded(Handle<A>),
+    Failed,
+}
+
+pub fn use_asset<A: Asset>(cx: &ScopeState, path: &str) -> AssetState<A> {
+    let world = use_world(cx);
+
+    let (current, handle, _) = cx.use_hook(|| {
+        let asset_server = world.borrow().resource::<AssetServer>().clone();
+        let handle = Rc::new(RefCell::new(asset_server.load::<A, _>(path.to_owned())));
+        let mut last_state = asset_server.get_load_state(handle.borrow().id());
+
+        // Failing to load doesn't send any AssetEvent, so follow load state instead
+        let check_handle = handle.clone();
+        let subscription = subscribe(cx, move |world| {
+            let state = world.resource::<AssetServer>().get_load_state(check_handle.borrow().id());
+            let is_changed = state != last_state;
+            last_state = state;
+            is_changed
+        });
+
+        (path.to_owned(), handle, subscription)
+    });
+
+    if current != path {
+        *current = path.to_owned();
+        *handle.borrow_mut() = world.borrow().resource::<AssetServer>().load(path.to_owned());
+    }
+
+    let handle = handle.borrow().clone();
+    let state = world.borrow().resource::<AssetServer>().get_load_state(handle.id());
+
+    match state {
+        LoadState::Loaded => AssetState::Loaded(handle),
+        LoadState::Failed => AssetState::Failed,
+        _ => AssetState::Loading,
+    }
+}
+
+// Timers follow Time::elapsed, so they respect pausing and relative speed
+pub fn use_timeout(cx: &ScopeState, duration: Duration) -> bool {
+    let world = use_world(cx);
+
+    let (fired, _) = cx.use_hook(|| {
+        let fired = Rc::new(Cell::new(false));
+        let deadline = world.borrow().resource::<Time>().elapsed() + duration;
+
+        let check_fired = fired.clone();
+        let subscription = subscribe(cx, move |world| {
+            if check_fired.get() || world.resource::<Time>().elapsed() < deadline {
+                return false;
+            }
+            check_fired.set(true);
+            true
+        });
+
+        (fired, subscription)
+    });
+
+    fired.get()
+}
+
+pub fn use_interval(cx: &ScopeState, period: Duration) -> u32 {
+    let world = use_world(cx);
+
+    let (count, _) = cx.use_hook(|| {
+        let count = Rc::new(Cell::new(0));
+        let start = world.borrow().resource::<Time>().elapsed();
+
+        let check_count = count.clone();
+        let subscription = subscribe(cx, move |world| {
+            let elapsed = world.resource::<Time>().elapsed() - start;
+            let current = (elapsed.as_secs_f64() / period.as_secs_f64()) as u32;
+            let is_fired = current != check_count.get();
+            check_count.set(current);
+            is_fired
+        });
+
+        (count, subscription)
+    });
+
+    count.get()
+}
+
+pub fn use_system<In: 'static, Out: 'static, M>(
+    cx: &ScopeState,
+    system: impl IntoSystem<In, Out, M>,
+) -> &UseSystem<In, Out> {
+    let world = use_world(cx);
+
+    cx.use_hook(|| {
+        let mut system: BoxedSystem<In, Out> = Box::new(IntoSystem::into_system(system));
+        system.initialize(&mut world.borrow_mut());
+
+        UseSystem {
+            system: RefCell::new(system),
+        }
+    })
+}
+
+pub fn use_system_state<P: SystemParam + 'static>(cx: &ScopeState) -> &UseSystemState<P> {
+    let world = use_world(cx);
+
+    cx.use_hook(|| UseSystemState {
+        state: RefCell::new(SystemState::new(&mut world.borrow_mut())),
+    })
+}
+
+// Both take the world from use_world's borrow_mut, so they can only run while
+// rendering. Handlers should go through UseCommands::run_system instead
+pub struct UseSystem<In, Out> {
+    system: RefCell<BoxedSystem<In, Out>>,
+}
+
+impl<In: 'static, Out: 'static> UseSystem<In, Out> {
+    pub fn run(&self, world: &mut World, input: In) -> Out {
+        let mut system = self.system.borrow_mut();
+
+        let output = system.run(input, world);
+        system.apply_deferred(world);
+        output
+    }
+}
+
+pub struct UseSystemState<P: SystemParam + 'static> {
+    state: RefCell<SystemState<P>>,
+}
+
+impl<P: SystemParam + 'static> UseSystemState<P> {
+    pub fn with<R>(&self, world: &mut World, f: impl FnOnce(SystemParamItem<P>) -> R) -> R {
+        let mut state = self.state.borrow_mut();
+
+        let output = f(state.get_mut(world));
+        state.apply(world);
+        output
+    }
+}
+
+pub struct UseBoundComponent<T, F> {
+    entity: Entity,
+    lens: fn(&mut T) -> &mut F,
+    value: Option<F>,
+    commands: UseCommands,
+}
+
+impl<T: Component, F: Clone + Send + 'static> UseBoundComponent<T, F> {
+    // None when the entity is despawned or doesn't have the component
+    pub fn get(&self) -> Option<&F> {
+        self.value.as_ref()
+    }
+    pub fn set(&self, value: F) {
+        // Scope re-renders through the subscription once it's applied
+        let (entity, lens) = (self.entity, self.lens);
+        self.commands.add(move |world: &mut World| {
+            if let Some(mut component) = world.get_mut::<T>(entity) {
+                *lens(&mut component) = value;
+            }
+        });
+    }
+}
+
+pub struct UseResourceMut<R> {
+    value: R,
+    commands: UseCommands,
+}
+
+impl<R: Resource + Clone> UseResourceMut<R> {
+    // Value as of the last render
+    pub fn get(&self) -> R {
+        self.value.clone()
+    }
+    pub fn set(&self, value: R) {
+        // Going through Mut so change detection gets flagged
+        self.commands.add(move |world: &mut World| {
+            if let Some(mut resource) = world.get_resource_mut::<R>() {
+                *resource = value;
+            }
+        });
+    }
+    pub fn modify(&self, f: impl FnOnce(&mut R) + Send + 'static) {
+        self.commands.add(move |world: &mut World| {
+            if let Some(mut resource) = world.get_resource_mut::<R>() {
+                f(&mut resource);
+            }
+        });
+    }
+}
@@ -1,59 +1,187 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{cell::RefCell, rc::Rc, time::{Duration, Instant}};
 
-use bevy::prelude::{World, NodeBundle};
+use bevy::{prelude::{World, Entity, NodeBundle, default}, log::error, ecs::{system::CommandQueue, component::Tick}, ui::{Style, Node}, utils::HashMap};
 use dioxus::prelude::VirtualDom;
 
-use crate::{app_root::{AppRootComponent, AppRootElement}, integration_data::IntegrationData};
+use crate::{world_access::BevyWorld, app_root::{AppRootElement, DioxusRoot}, integration_data::IntegrationData, subscriptions::{BevySubscriptions, Subscriptions, component_change_tick}, commands::BevyCommandQueue, error::{IntegrationError, DioxusError}};
 
 pub struct Dioxus {
     vdom: VirtualDom,
     integration_data: IntegrationData,
+    root_changed: Option<Tick>,
 }
 
-pub type BevyWorld = Rc<RefCell<World>>;
+// Every mounted DioxusRoot, keyed by its entity
+#[derive(Default)]
+pub struct DioxusRoots {
+    roots: HashMap<Entity, Dioxus>,
+}
+
+#[cfg(test)]
+impl DioxusRoots {
+    pub fn integration_data(&self, root: Entity) -> &IntegrationData {
+        &self.roots[&root].integration_data
+    }
+    pub fn is_mounted(&self, root: Entity) -> bool {
+        self.roots.contains_key(&root)
+    }
+}
 
-pub fn setup_dioxus(world: &mut World) {
-    let app_root = world.get_resource::<AppRootComponent>()
-        .expect("AppRoot resource should exist");
+// How long a frame can spend on polling tasks woken during rendering
+const TASK_BUDGET: Duration = Duration::from_millis(2);
+
+pub fn setup_dioxus(world: &mut World, root: DioxusRoot, root_style: Style) {
+    world.spawn((
+        NodeBundle {
+            style: root_style,
+            ..default()
+        },
+        AppRootElement,
+        root,
+    ));
+}
 
-    let mut vdom = VirtualDom::new(app_root.0.clone());
-    
-    vdom.base_scope().provide_context(Rc::new(RefCell::new(World::new())));
-    let context_world = vdom.base_scope().consume_context::<BevyWorld>().unwrap();
-    std::mem::swap(world, &mut context_world.borrow_mut());
+pub fn update_dioxus(world: &mut World) {
+    let mut roots = world
+        .remove_non_send_resource::<DioxusRoots>()
+        .unwrap_or_default();
 
-    let mutations = vdom.rebuild();
+    unmount_roots(world, &mut roots);
+    mount_roots(world, &mut roots);
 
-    let mut integration_data = IntegrationData::default();
-    let root_entity = context_world.borrow_mut().spawn((NodeBundle::default(), AppRootElement)).id();
-    integration_data.set_root(root_entity);
+    // Budget is shared between roots
+    let start = Instant::now();
 
-    integration_data.update_dom(&mut context_world.borrow_mut(), mutations);
+    for (&entity, dioxus) in roots.roots.iter_mut() {
+        update_root_props(world, entity, dioxus);
+        dioxus.update(world, entity, start);
+    }
 
-    std::mem::swap(world, &mut context_world.borrow_mut());
+    // Applied after mutations so commands see the updated UI entities, and
+    // once roots are back so DespawnDioxusRoot from the UI finds its root
+    let queues: Vec<BevyCommandQueue> = roots.roots.values().map(Dioxus::command_queue).collect();
+    world.insert_non_send_resource(roots);
 
-    world.insert_non_send_resource(Dioxus {
-        vdom,
-        integration_data,
-    });
+    for queue in queues {
+        queue.borrow_mut().apply(world);
+    }
 }
 
-pub fn update_dioxus(world: &mut World) {
-    let mut dioxus = world
-        .remove_non_send_resource::<Dioxus>()
-        .expect("Dioxus resource should exist");
+fn mount_roots(world: &mut World, roots: &mut DioxusRoots) {
+    let added: Vec<(Entity, DioxusRoot)> = world
+        .query::<(Entity, &DioxusRoot)>()
+        .iter(world)
+        .filter(|(entity, _)| !roots.roots.contains_key(entity))
+        .map(|(entity, root)| (entity, root.clone()))
+        .collect();
+
+    for (entity, root) in added {
+        // Existing UI entities keep their own node, and their
+        // children stay in front of the mounted tree
+        if world.get::<Node>(entity).is_none() {
+            world.entity_mut(entity).insert(NodeBundle::default());
+        }
+
+        let dioxus = Dioxus::new(world, entity, root.create_vdom());
+        roots.roots.insert(entity, dioxus);
+    }
+}
 
-    let context_world = dioxus.vdom.base_scope().consume_context::<BevyWorld>().unwrap();
-    std::mem::swap(world, &mut context_world.borrow_mut());
+fn unmount_roots(world: &mut World, roots: &mut DioxusRoots) {
+    roots.roots.retain(|&entity, dioxus| {
+        let Some(entity) = world.get_entity(entity) else {
+            dioxus.integration_data.despawn_orphaned_portals(world);
+            return false;
+        };
 
-    let mutations = dioxus.vdom.render_immediate();
-    let mut integration_data = std::mem::take(&mut dioxus.integration_data);
+        if entity.contains::<DioxusRoot>() {
+            return true;
+        }
 
-    integration_data.update_dom(&mut context_world.borrow_mut(), mutations);
+        // Root entity outlives the tree when only DioxusRoot gets removed
+        dioxus.integration_data.unmount(world);
+        false
+    });
+}
 
-    std::mem::swap(world, &mut context_world.borrow_mut());
+// Same as removing DioxusRoot, without waiting for update_dioxus
+pub fn unmount_root(world: &mut World, entity: Entity) {
+    let Some(mut roots) = world.get_non_send_resource_mut::<DioxusRoots>() else {
+        return;
+    };
+    let Some(mut dioxus) = roots.roots.remove(&entity) else {
+        return;
+    };
+
+    dioxus.integration_data.unmount(world);
+}
 
-    dioxus.integration_data = integration_data;
+fn update_root_props(world: &mut World, entity: Entity, dioxus: &mut Dioxus) {
+    // Changing DioxusRoot, like through set_props, re-renders the mounted root
+    let changed = component_change_tick::<DioxusRoot>(world, entity);
+    if changed == dioxus.root_changed {
+        return;
+    }
+    dioxus.root_changed = changed;
+
+    if let Some(root) = world.get::<DioxusRoot>(entity) {
+        root.update_vdom(&mut dioxus.vdom);
+    }
+}
+
+impl Dioxus {
+    fn new(world: &mut World, root_entity: Entity, mut vdom: VirtualDom) -> Self {
+        let context_world = vdom.base_scope().provide_context(BevyWorld::default());
+        vdom.base_scope().provide_context::<BevySubscriptions>(Rc::new(RefCell::new(Subscriptions::default())));
+        vdom.base_scope().provide_context::<BevyCommandQueue>(Rc::new(RefCell::new(CommandQueue::default())));
+
+        let mutations = context_world.with_bound(world, || vdom.rebuild());
+
+        let mut integration_data = IntegrationData::default();
+        integration_data.set_root(root_entity);
+
+        if let Err(error) = integration_data.update_dom(world, mutations) {
+            report_error(world, root_entity, error);
+        }
+
+        Self {
+            vdom,
+            integration_data,
+            root_changed: component_change_tick::<DioxusRoot>(world, root_entity),
+        }
+    }
+    fn update(&mut self, world: &mut World, root_entity: Entity, start: Instant) {
+        let subscriptions = self.vdom.base_scope().consume_context::<BevySubscriptions>().unwrap();
+        subscriptions.borrow_mut().run(world);
+
+        let context_world = self.vdom.base_scope().consume_context::<BevyWorld>().unwrap();
+
+        // render_immediate processes queued events and only runs dirty scopes,
+        // from subscriptions above or from tasks and state changes, so a clean
+        // vdom comes back without mutations. Tasks woken while rendering get
+        // another go while there is budget left
+        loop {
+            // World is only bound while rendering, update_dom uses it directly
+            let vdom = &mut self.vdom;
+            let mutations = context_world.with_bound(world, || vdom.render_immediate());
+            if mutations.edits.is_empty() && mutations.templates.is_empty() {
+                break;
+            }
+            if let Err(error) = self.integration_data.update_dom(world, mutations) {
+                report_error(world, root_entity, error);
+            }
+
+            if start.elapsed() >= TASK_BUDGET {
+                break;
+            }
+        }
+    }
+    fn command_queue(&self) -> BevyCommandQueue {
+        self.vdom.base_scope().consume_context::<BevyCommandQueue>().unwrap()
+    }
+}
 
-    world.insert_non_send_resource(dioxus);
+fn report_error(world: &mut World, root: Entity, error: IntegrationError) {
+    error!("failed to update Dioxus root {root:?}: {error}");
+    world.send_event(DioxusError { root, error });
 }
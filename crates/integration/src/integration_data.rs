@@ -1,7 +1,7 @@
-use bevy::{prelude::{World, BuildWorldChildren, Entity, Parent, Children, DespawnRecursiveExt}, text::{Text, TextStyle, TextSection}, ui::Style};
+use bevy::{log::warn, prelude::{World, BuildWorldChildren, Entity, Parent, Children, DespawnRecursiveExt, NodeBundle, default}, ecs::world::EntityMut, text::{Text, TextStyle, TextSection}, ui::Style};
 use dioxus::core::{Mutations, Mutation, ElementId, BorrowedAttributeValue};
 
-use crate::{template_map::TemplateMap, element_map::ElementMap, ui_node::{Element, NodeChild, ChildNode, RootNode}, bevy_node::TextNode, attributes::Attr};
+use crate::{template_map::TemplateMap, element_map::ElementMap, ui_node::{Element, NodeChild, ChildNode, RootNode}, bevy_node::{TextNode, EntityProxy, Portal, PortalContent, EventListeners}, attributes::Attr, error::IntegrationError};
 
 #[derive(Default, Debug)]
 pub struct IntegrationData {
@@ -15,9 +15,65 @@ impl IntegrationData {
         self.element_map.set(ElementId(0), root);
         self.stack.push(root);
     }
-    pub fn update_dom(&mut self, world: &mut World, mutations: Mutations) {
+    pub fn unmount(&mut self, world: &mut World) {
+        // Root can be an existing entity with children of its own,
+        // only despawn the ones this tree created
+        let Ok(root) = self.element_map.get(ElementId(0)) else {
+            return;
+        };
+        let Some(children) = world.get::<Children>(root) else {
+            return;
+        };
+
+        let owned: Vec<Entity> = children
+            .iter()
+            .copied()
+            .filter(|&child| self.element_map.contains(child))
+            .collect();
+
+        for child in owned {
+            despawn_node(world, &mut self.element_map, child);
+        }
+    }
+    // Portal contents aren't descendants of the root, so when the root gets
+    // despawned directly they stay around until cleaned up here
+    pub fn despawn_orphaned_portals(&mut self, world: &mut World) {
+        let orphaned: Vec<Entity> = world
+            .query::<(Entity, &PortalContent)>()
+            .iter(world)
+            .filter(|(_, content)| world.get_entity(content.portal).is_none())
+            .map(|(entity, _)| entity)
+            .collect();
+
+        for content in orphaned {
+            despawn_node(world, &mut self.element_map, content);
+        }
+    }
+    // Stops at the first edit that fails, the rest would likely
+    // depend on it and fail in more confusing ways
+    pub fn update_dom(&mut self, world: &mut World, mutations: Mutations) -> Result<(), IntegrationError> {
+        let result = self.apply_mutations(world, mutations);
+        if result.is_err() {
+            self.reset_stack(world);
+        }
+
+        result
+    }
+    fn reset_stack(&mut self, world: &mut World) {
+        // Nodes created by the failed batch were never attached, so they'd
+        // show up as top level UI nodes and carry over to the next batch.
+        // Pushed roots are already in the tree and stay as they are
+        let leftover = self.stack.split_off(1.min(self.stack.len()));
+
+        for entity in leftover {
+            if world.get::<Parent>(entity).is_none() {
+                despawn_node(world, &mut self.element_map, entity);
+            }
+        }
+    }
+    fn apply_mutations(&mut self, world: &mut World, mutations: Mutations) -> Result<(), IntegrationError> {
         for template in mutations.templates {
-            self.template_map.add(template);
+            self.template_map.add(template)?;
         }
     
         for edit in mutations.edits {
@@ -46,36 +102,50 @@ impl IntegrationData {
                     => self.set_attribute(world, name, value, id),
                 Mutation::SetText { value, id } =>
                     self.set_text(world, value, id),
-                Mutation::NewEventListener { name, id } => todo!(),
-                Mutation::RemoveEventListener { name, id } => todo!(),
+                Mutation::NewEventListener { name, id }
+                    => self.new_event_listener(world, name, id),
+                Mutation::RemoveEventListener { name, id }
+                    => self.remove_event_listener(world, name, id),
                 Mutation::Remove { id }
                     => self.remove(world, id),
                 Mutation::PushRoot { id }
                     => self.push_root(world, id),
-            _ => (),
-            }
+                // Every variant is handled, this is for ones added in
+                // newer Dioxus versions that would otherwise desync silently
+                #[allow(unreachable_patterns)]
+                edit => {
+                    warn!("unsupported mutation {edit:?}");
+                    Ok(())
+                },
+            }?;
         }
+
+        Ok(())
     }
-    fn append_children(&mut self, world: &mut World, id: ElementId, m: usize) {
-        let children = self.stack.split_off(self.stack.len() - m);
-        let parent = self.element_map.get(id);
+    fn append_children(&mut self, world: &mut World, id: ElementId, m: usize) -> Result<(), IntegrationError> {
+        let children = self.pop_nodes(m)?;
+        let parent = children_parent(world, self.element_map.get(id)?);
 
-        let mut parent = world.entity_mut(parent);
+        let mut parent = entity_mut(world, parent)?;
 
         for child in children {
             parent.add_child(child);
         }
+
+        Ok(())
     }
-    fn assign_id(&mut self, world: &mut World, path: &[u8], id: ElementId) {
-        let child = child_at_path(&self.stack, world, path);
+    fn assign_id(&mut self, world: &mut World, path: &[u8], id: ElementId) -> Result<(), IntegrationError> {
+        let child = child_at_path(&self.stack, world, path)?;
         self.element_map.set(id, child);
+        Ok(())
     }
-    fn create_placeholder(&mut self, world: &mut World, id: ElementId) {
+    fn create_placeholder(&mut self, world: &mut World, id: ElementId) -> Result<(), IntegrationError> {
         let entity = world.spawn_empty().id();
         self.element_map.set(id, entity);
         self.stack.push(entity);
+        Ok(())
     }
-    fn create_text_node(&mut self, world: &mut World, value: &str, id: ElementId) {
+    fn create_text_node(&mut self, world: &mut World, value: &str, id: ElementId) -> Result<(), IntegrationError> {
         let node = TextNode {
             text: Text::from_section(value, TextStyle::default()),
         };
@@ -83,26 +153,44 @@ impl IntegrationData {
         
         self.element_map.set(id, entity);
         self.stack.push(entity);
+        Ok(())
     }
-    fn hydrate_text(&mut self, world: &mut World, path: &[u8], value: &str, id: ElementId) {
-        let entity = child_at_path(&self.stack, world, path);
-        self.element_map.set(id, entity);
+    fn hydrate_text(&mut self, world: &mut World, path: &[u8], value: &str, id: ElementId) -> Result<(), IntegrationError> {
+        let entity = child_at_path(&self.stack, world, path)?;
         
         if let Some(mut text) = world.get_mut::<Text>(entity) {
             // Same as set_text
             text.sections[0].value = value.to_owned();
-        } else {
-            // Do we need to preserve node styles?
-            world.entity_mut(entity).despawn_recursive();
+            self.element_map.set(id, entity);
+            return Ok(());
+        }
 
-            let node = TextNode {
-                text: Text::from_section(value, TextStyle::default()),
-            };
-            world.spawn(node.bundle());
+        // Do we need to preserve node styles?
+        let node = TextNode {
+            text: Text::from_section(value, TextStyle::default()),
+        };
+        let text = world.spawn(node.bundle()).id();
+
+        // Takes the node's place, template roots don't have a parent yet
+        // so they get replaced on the stack instead
+        match world.get::<Parent>(entity) {
+            Some(parent) => {
+                let parent = parent.get();
+                add_children_relative(world, parent, entity, vec![text], ChildRelation::Before)?;
+            },
+            None => {
+                if let Some(root) = self.stack.iter_mut().rev().find(|root| **root == entity) {
+                    *root = text;
+                }
+            },
         }
+
+        despawn_node(world, &mut self.element_map, entity);
+        self.element_map.set(id, text);
+        Ok(())
     }
-    fn load_template(&mut self, world: &mut World, name: &str, index: usize, id: ElementId) {
-        let entity = match &self.template_map.map[name][index] {
+    fn load_template(&mut self, world: &mut World, name: &str, index: usize, id: ElementId) -> Result<(), IntegrationError> {
+        let entity = match self.template_map.get(name, index)? {
             RootNode::ElementWithChildren {
                 element,
                 children,
@@ -111,6 +199,8 @@ impl IntegrationData {
                     Element::Div { node } => world.spawn(node.bundle()),
                     Element::Image { node } => world.spawn(node.bundle()),
                     Element::Button { node } => world.spawn(node.bundle()),
+                    Element::Entity { node } => world.spawn(node.bundle()),
+                    Element::Portal { node } => world.spawn(node.bundle()),
                 }.id();
 
                 let mut current = parent;
@@ -120,12 +210,15 @@ impl IntegrationData {
                 for node_child in &children.nodes {
                     match node_child {
                         NodeChild::Node(node) => {
-                            world.entity_mut(current).with_children(|builder| {
+                            let parent = children_parent(world, current);
+                            entity_mut(world, parent)?.with_children(|builder| {
                                 created = match node {
                                     ChildNode::Element { element } => match element {
                                         Element::Div { node } => builder.spawn(node.bundle()),
                                         Element::Image { node } => builder.spawn(node.bundle()),
                                         Element::Button { node } => builder.spawn(node.bundle()),
+                                        Element::Entity { node } => builder.spawn(node.bundle()),
+                                        Element::Portal { node } => builder.spawn(node.bundle()),
                                     },
                                     ChildNode::Text { node } => builder.spawn(node.bundle()),
                                     ChildNode::PlaceHolder => builder.spawn_empty(),
@@ -137,7 +230,9 @@ impl IntegrationData {
                             current = created;
                         },
                         NodeChild::Out => {
-                            current = stack.pop().expect("shouldn't have empty vec at this stage");
+                            current = stack
+                                .pop()
+                                .ok_or_else(|| IntegrationError::MalformedTemplate(name.to_owned()))?;
                         },
                     }
                 }
@@ -149,6 +244,8 @@ impl IntegrationData {
                     Element::Div { node } => world.spawn(node.bundle()),
                     Element::Image { node } => world.spawn(node.bundle()),
                     Element::Button { node } => world.spawn(node.bundle()),
+                    Element::Entity { node } => world.spawn(node.bundle()),
+                    Element::Portal { node } => world.spawn(node.bundle()),
                 }.id()
             },
             RootNode::Text { node } => {
@@ -159,39 +256,42 @@ impl IntegrationData {
 
         self.element_map.set(id, entity);
         self.stack.push(entity);
+        Ok(())
     }
-    fn replace_with(&mut self, world: &mut World, id: ElementId, m: usize) {
-        let to_replace = self.stack.split_off(self.stack.len() - m);
-        let old = self.element_map.get(id);
+    fn replace_with(&mut self, world: &mut World, id: ElementId, m: usize) -> Result<(), IntegrationError> {
+        let to_replace = self.pop_nodes(m)?;
+        let old = self.element_map.get(id)?;
 
         // Not the most performant impl, but Children's
         // field is private
-        let parent = parent_entity(world, old);
-        add_children_relative(world, parent, old, to_replace, ChildRelation::Before);
-        despawn_child(world, old);
-    }
-    fn replace_placeholder(&mut self, world: &mut World, path: &[u8], m: usize) {
-        let to_replace = self.stack.split_off(self.stack.len() - m);
-        let child = child_at_path(&self.stack, world, path);
-        let parent = parent_entity(world, child);
+        let parent = parent_entity(world, old)?;
+        add_children_relative(world, parent, old, to_replace, ChildRelation::Before)?;
+        despawn_node(world, &mut self.element_map, old);
+        Ok(())
+    }
+    fn replace_placeholder(&mut self, world: &mut World, path: &[u8], m: usize) -> Result<(), IntegrationError> {
+        let to_replace = self.pop_nodes(m)?;
+        let child = child_at_path(&self.stack, world, path)?;
+        let parent = parent_entity(world, child)?;
         
         // Same as replace_with
-        add_children_relative(world, parent, child, to_replace, ChildRelation::Before);
-        despawn_child(world, child);
+        add_children_relative(world, parent, child, to_replace, ChildRelation::Before)?;
+        despawn_node(world, &mut self.element_map, child);
+        Ok(())
     }
-    fn insert_after(&mut self, world: &mut World, id: ElementId, m: usize) {
-        let to_insert = self.stack.split_off(self.stack.len() - m);
-        let old = self.element_map.get(id);
+    fn insert_after(&mut self, world: &mut World, id: ElementId, m: usize) -> Result<(), IntegrationError> {
+        let to_insert = self.pop_nodes(m)?;
+        let old = self.element_map.get(id)?;
 
-        let parent = parent_entity(world, old);
-        add_children_relative(world, parent, old, to_insert, ChildRelation::After);
+        let parent = parent_entity(world, old)?;
+        add_children_relative(world, parent, old, to_insert, ChildRelation::After)
     }
-    fn insert_before(&mut self, world: &mut World, id: ElementId, m: usize) {
-        let to_insert = self.stack.split_off(self.stack.len() - m);
-        let old = self.element_map.get(id);
+    fn insert_before(&mut self, world: &mut World, id: ElementId, m: usize) -> Result<(), IntegrationError> {
+        let to_insert = self.pop_nodes(m)?;
+        let old = self.element_map.get(id)?;
 
-        let parent = parent_entity(world, old);
-        add_children_relative(world, parent, old, to_insert, ChildRelation::Before);
+        let parent = parent_entity(world, old)?;
+        add_children_relative(world, parent, old, to_insert, ChildRelation::Before)
     }
     fn set_attribute(
         &mut self,
@@ -199,53 +299,60 @@ impl IntegrationData {
         name: &str,
         value: BorrowedAttributeValue,
         id: ElementId,
-    ) {
-        let entity = self.element_map.get(id);
+    ) -> Result<(), IntegrationError> {
+        let entity = self.element_map.get(id)?;
 
-        match name {
-            "display" => update_style(world, entity, value, |s| &mut s.display),
-            "position_type" => update_style(world, entity, value, |s| &mut s.position_type),
-            "overflow" => update_style(world, entity, value, |s| &mut s.overflow),
-            "direction" => update_style(world, entity, value, |s| &mut s.direction),
-            "left" => update_style(world, entity, value, |s| &mut s.left),
-            "right" => update_style(world, entity, value, |s| &mut s.right),
-            "top" => update_style(world, entity, value, |s| &mut s.top),
-            "bottom" => update_style(world, entity, value, |s| &mut s.bottom),
-            "width" => update_style(world, entity, value, |s| &mut s.width),
-            "height" => update_style(world, entity, value, |s| &mut s.height),
-            "min_width" => update_style(world, entity, value, |s| &mut s.min_width),
-            "min_height" => update_style(world, entity, value, |s| &mut s.min_height),
-            "max_width" => update_style(world, entity, value, |s| &mut s.max_width),
-            "max_height" => update_style(world, entity, value, |s| &mut s.max_height),
-            "aspect_ratio" => update_style(world, entity, value, |s| &mut s.aspect_ratio),
-            "align_items" => update_style(world, entity, value, |s| &mut s.align_items),
-            "justify_items" => update_style(world, entity, value, |s| &mut s.justify_items),
-            "align_self" => update_style(world, entity, value, |s| &mut s.align_self),
-            "justify_self" => update_style(world, entity, value, |s| &mut s.justify_self),
-            "align_content" => update_style(world, entity, value, |s| &mut s.align_content),
-            "justify_content" => update_style(world, entity, value, |s| &mut s.justify_content),
-            "margin" => update_style(world, entity, value, |s| &mut s.margin),
-            "padding" => update_style(world, entity, value, |s| &mut s.padding),
-            "border" => update_style(world, entity, value, |s| &mut s.border),
-            "flex_direction" => update_style(world, entity, value, |s| &mut s.flex_direction),
-            "flex_wrap" => update_style(world, entity, value, |s| &mut s.flex_wrap),
-            "flex_grow" => update_style(world, entity, value, |s| &mut s.flex_grow),
-            "flex_shrink" => update_style(world, entity, value, |s| &mut s.flex_shrink),
-            "flex_basis" => update_style(world, entity, value, |s| &mut s.flex_basis),
-            "row_gap" => update_style(world, entity, value, |s| &mut s.row_gap),
-            "column_gap" => update_style(world, entity, value, |s| &mut s.column_gap),
-            "grid_auto_flow" => update_style(world, entity, value, |s| &mut s.grid_auto_flow),
-            "grid_template_rows" => update_style(world, entity, value, |s| &mut s.grid_template_rows),
-            "grid_template_columns" => update_style(world, entity, value, |s| &mut s.grid_template_columns),
-            "grid_auto_rows" => update_style(world, entity, value, |s| &mut s.grid_auto_rows),
-            "grid_auto_columns" => update_style(world, entity, value, |s| &mut s.grid_auto_columns),
-            "grid_row" => update_style(world, entity, value, |s| &mut s.grid_row),
-            "grid_column" => update_style(world, entity, value, |s| &mut s.grid_column),
-            _ => panic!("invalid attribute name"),
+        // Portal itself is hidden, so its styles go to its content
+        let entity = match name {
+            "target" => return set_portal_target(world, entity, value),
+            _ => children_parent(world, entity),
         };
+
+        match name {
+            "display" => update_style(world, entity, name, value, |s| &mut s.display),
+            "position_type" => update_style(world, entity, name, value, |s| &mut s.position_type),
+            "overflow" => update_style(world, entity, name, value, |s| &mut s.overflow),
+            "direction" => update_style(world, entity, name, value, |s| &mut s.direction),
+            "left" => update_style(world, entity, name, value, |s| &mut s.left),
+            "right" => update_style(world, entity, name, value, |s| &mut s.right),
+            "top" => update_style(world, entity, name, value, |s| &mut s.top),
+            "bottom" => update_style(world, entity, name, value, |s| &mut s.bottom),
+            "width" => update_style(world, entity, name, value, |s| &mut s.width),
+            "height" => update_style(world, entity, name, value, |s| &mut s.height),
+            "min_width" => update_style(world, entity, name, value, |s| &mut s.min_width),
+            "min_height" => update_style(world, entity, name, value, |s| &mut s.min_height),
+            "max_width" => update_style(world, entity, name, value, |s| &mut s.max_width),
+            "max_height" => update_style(world, entity, name, value, |s| &mut s.max_height),
+            "aspect_ratio" => update_style(world, entity, name, value, |s| &mut s.aspect_ratio),
+            "align_items" => update_style(world, entity, name, value, |s| &mut s.align_items),
+            "justify_items" => update_style(world, entity, name, value, |s| &mut s.justify_items),
+            "align_self" => update_style(world, entity, name, value, |s| &mut s.align_self),
+            "justify_self" => update_style(world, entity, name, value, |s| &mut s.justify_self),
+            "align_content" => update_style(world, entity, name, value, |s| &mut s.align_content),
+            "justify_content" => update_style(world, entity, name, value, |s| &mut s.justify_content),
+            "margin" => update_style(world, entity, name, value, |s| &mut s.margin),
+            "padding" => update_style(world, entity, name, value, |s| &mut s.padding),
+            "border" => update_style(world, entity, name, value, |s| &mut s.border),
+            "flex_direction" => update_style(world, entity, name, value, |s| &mut s.flex_direction),
+            "flex_wrap" => update_style(world, entity, name, value, |s| &mut s.flex_wrap),
+            "flex_grow" => update_style(world, entity, name, value, |s| &mut s.flex_grow),
+            "flex_shrink" => update_style(world, entity, name, value, |s| &mut s.flex_shrink),
+            "flex_basis" => update_style(world, entity, name, value, |s| &mut s.flex_basis),
+            "row_gap" => update_style(world, entity, name, value, |s| &mut s.row_gap),
+            "column_gap" => update_style(world, entity, name, value, |s| &mut s.column_gap),
+            "grid_auto_flow" => update_style(world, entity, name, value, |s| &mut s.grid_auto_flow),
+            "grid_template_rows" => update_style(world, entity, name, value, |s| &mut s.grid_template_rows),
+            "grid_template_columns" => update_style(world, entity, name, value, |s| &mut s.grid_template_columns),
+            "grid_auto_rows" => update_style(world, entity, name, value, |s| &mut s.grid_auto_rows),
+            "grid_auto_columns" => update_style(world, entity, name, value, |s| &mut s.grid_auto_columns),
+            "grid_row" => update_style(world, entity, name, value, |s| &mut s.grid_row),
+            "grid_column" => update_style(world, entity, name, value, |s| &mut s.grid_column),
+            "entity" => set_proxied_entity(world, entity, value),
+            _ => Err(IntegrationError::UnknownAttribute(name.to_owned())),
+        }
     }
-    fn set_text(&mut self, world: &mut World, value: &str, id: ElementId) {
-        let entity = self.element_map.get(id);
+    fn set_text(&mut self, world: &mut World, value: &str, id: ElementId) -> Result<(), IntegrationError> {
+        let entity = self.element_map.get(id)?;
         if let Some(mut text) = world.get_mut::<Text>(entity) {
             // Multi section text with DOM wouldn't be compatible
             // So assuming every text consists of 1 section is OK
@@ -253,54 +360,164 @@ impl IntegrationData {
             let style = text.sections[0].style.clone();
             text.sections = vec![TextSection::new(value, style)];
         }
+
+        Ok(())
+    }
+    fn new_event_listener(&mut self, world: &mut World, name: &str, id: ElementId) -> Result<(), IntegrationError> {
+        let entity = self.element_map.get(id)?;
+        let mut entity = entity_mut(world, entity)?;
+
+        match entity.get_mut::<EventListeners>() {
+            Some(mut listeners) => {
+                listeners.names.insert(name.to_owned());
+            },
+            None => {
+                let mut listeners = EventListeners::default();
+                listeners.names.insert(name.to_owned());
+                entity.insert(listeners);
+            },
+        }
+
+        Ok(())
+    }
+    fn remove_event_listener(&mut self, world: &mut World, name: &str, id: ElementId) -> Result<(), IntegrationError> {
+        let entity = self.element_map.get(id)?;
+
+        if let Some(mut listeners) = world.get_mut::<EventListeners>(entity) {
+            listeners.names.remove(name);
+        }
+
+        Ok(())
     }
-    fn remove(&mut self, world: &mut World, id: ElementId) {
-        let entity = self.element_map.get(id);
-        world.entity_mut(entity).despawn_recursive();
+    fn remove(&mut self, world: &mut World, id: ElementId) -> Result<(), IntegrationError> {
+        let entity = self.element_map.get(id)?;
+        despawn_node(world, &mut self.element_map, entity);
+        Ok(())
     }
-    fn push_root(&mut self, world: &mut World, id: ElementId) {
-        let entity = self.element_map.get(id);
+    fn push_root(&mut self, world: &mut World, id: ElementId) -> Result<(), IntegrationError> {
+        let entity = self.element_map.get(id)?;
         self.stack.push(entity);
+        Ok(())
+    }
+    fn pop_nodes(&mut self, m: usize) -> Result<Vec<Entity>, IntegrationError> {
+        let at = self.stack
+            .len()
+            .checked_sub(m)
+            .ok_or(IntegrationError::StackUnderflow(m))?;
+
+        Ok(self.stack.split_off(at))
     }
 }
 
-fn child_at_path(stack: &Vec<Entity>, world: &mut World, path: &[u8]) -> Entity {
+fn child_at_path(stack: &Vec<Entity>, world: &mut World, path: &[u8]) -> Result<Entity, IntegrationError> {
     let mut current = stack
         .last()
-        .expect("stack shouldn't be empty")
+        .ok_or(IntegrationError::StackUnderflow(1))?
         .to_owned();
     
     // Maybe map parent <-> children relationship outside of ecs too?
     for &index in path {
-        current = children_at(world, current, index as usize);
+        current = children_at(world, current, index as usize)?;
     }
 
-    current
+    Ok(current)
 }
 
-fn despawn_child(world: &mut World, child: Entity) {
-    // Maybe use HierarchyEvent::ChildRemoved directly?
-    let mut child = world.entity_mut(child);
-    child.remove_parent();
-    child.despawn();
+fn despawn_node(world: &mut World, element_map: &mut ElementMap, entity: Entity) {
+    // Dioxus only sends the removed node, its descendants go with it
+    let portal_contents = release_subtree(world, element_map, entity);
+    despawn_recursive(world, entity);
+
+    for content in portal_contents {
+        despawn_recursive(world, content);
+    }
+}
+
+fn despawn_recursive(world: &mut World, entity: Entity) {
+    // Anything could have despawned it already, which is fine here
+    if let Some(entity) = world.get_entity_mut(entity) {
+        entity.despawn_recursive();
+    }
+}
+
+fn entity_mut(world: &mut World, entity: Entity) -> Result<EntityMut<'_>, IntegrationError> {
+    world
+        .get_entity_mut(entity)
+        .ok_or(IntegrationError::MissingEntity(entity))
+}
+
+fn release_subtree(world: &mut World, element_map: &mut ElementMap, entity: Entity) -> Vec<Entity> {
+    // Entities placed with the entity element are managed outside, so they shouldn't
+    // go down with the tree. Portal contents aren't descendants, so they're
+    // returned to be despawned along with it
+    let mut portal_contents = Vec::new();
+    let mut stack = vec![entity];
+
+    while let Some(current) = stack.pop() {
+        element_map.remove_entity(current);
+
+        if let Some(EntityProxy { entity: Some(proxied) }) = world.get::<EntityProxy>(current) {
+            let proxied = *proxied;
+            if let Some(mut proxied) = world.get_entity_mut(proxied) {
+                proxied.remove_parent();
+            }
+        }
+
+        if let Some(Portal { content: Some(content), .. }) = world.get::<Portal>(current) {
+            portal_contents.push(*content);
+            stack.push(*content);
+        }
+
+        if let Some(children) = world.get::<Children>(current) {
+            stack.extend(children.iter());
+        }
+    }
+
+    portal_contents
+}
+
+fn children_parent(world: &mut World, entity: Entity) -> Entity {
+    // Portal's children live under its content, which can be anywhere
+    let Some(portal) = world.get::<Portal>(entity) else {
+        return entity;
+    };
+
+    // Content goes away with its target, spawn a new one in that case
+    if let Some(content) = portal.content.filter(|&content| world.get_entity(content).is_some()) {
+        return content;
+    }
+
+    let style = portal.content_style.clone();
+    let content = world
+        .spawn((
+            NodeBundle {
+                style,
+                ..default()
+            },
+            PortalContent { portal: entity },
+        ))
+        .id();
+    if let Some(mut portal) = world.get_mut::<Portal>(entity) {
+        portal.content = Some(content);
+    }
+    content
 }
 
-fn parent_entity(world: &mut World, child: Entity) -> Entity {
-    // I think we can safely assume this would never panic without a bug
+fn parent_entity(world: &mut World, child: Entity) -> Result<Entity, IntegrationError> {
     world
         .get::<Parent>(child)
-        .expect("parent should exist")
-        .get()
+        .map(|parent| parent.get())
+        .ok_or(IntegrationError::MissingParent(child))
 }
 
-fn children_at(world: &mut World, parent: Entity, index: usize) -> Entity {
+fn children_at(world: &mut World, parent: Entity, index: usize) -> Result<Entity, IntegrationError> {
+    let parent = children_parent(world, parent);
+
     world
         .get::<Children>(parent)
-        .expect("children should exist")
-        .get(index)
-        .expect("child should exist")
-        .to_owned()
-
+        .and_then(|children| children.get(index))
+        .copied()
+        .ok_or(IntegrationError::MissingChild { parent, index })
 }
 
 enum ChildRelation {
@@ -314,51 +531,757 @@ fn add_children_relative(
     child: Entity,
     children: Vec<Entity>,
     child_relation: ChildRelation,
-) {
-    // I think we can safely assume this would never panic without a bug
-    let children_components = world
+) -> Result<(), IntegrationError> {
+    // Keyed lists move existing siblings, which insert_children takes out
+    // before inserting, so the index has to be counted without them
+    let index = world
         .get::<Children>(parent)
-        .expect("children should exist");
-
-    let index = children_components.iter()
-        .position(|entity| *entity == child)
-        .expect("child should exist");
+        .and_then(|children_components| {
+            children_components
+                .iter()
+                .filter(|entity| !children.contains(entity))
+                .position(|entity| *entity == child)
+        })
+        .ok_or(IntegrationError::NotAChild { parent, child })?;
 
     let index = match child_relation {
         ChildRelation::Before => index,
         ChildRelation::After => index + 1,
     };
 
-    world.entity_mut(parent).insert_children(index, &children);
+    entity_mut(world, parent)?.insert_children(index, &children);
+    Ok(())
 }
 
 fn update_style<T: Default + Clone + 'static>(
     world: &mut World,
     entity: Entity,
+    name: &str,
     value: BorrowedAttributeValue,
     selector: fn(&mut Style) -> &mut T,
-) {
+) -> Result<(), IntegrationError> {
     use BorrowedAttributeValue as Val;
-    let style = world.get_mut::<Style>(entity);
 
-    let mut style = match style {
-        Some(style) => style,
+    // Checked before touching Style, so a bad value leaves it as it was
+    let value = match value {
+        Val::Any(value) => value
+            .as_any()
+            .downcast_ref::<Attr<T>>()
+            .ok_or_else(|| IntegrationError::InvalidAttributeValue(name.to_owned()))?
+            .0
+            .clone(),
+        Val::None => T::default(),
+        _ => return Err(IntegrationError::InvalidAttributeValue(name.to_owned())),
+    };
+
+    let mut entity = entity_mut(world, entity)?;
+
+    match entity.get_mut::<Style>() {
+        Some(mut style) => *selector(&mut style) = value,
         None => {
-            world.entity_mut(entity).insert(Style::default());
-            world.get_mut(entity).unwrap()
+            let mut style = Style::default();
+            *selector(&mut style) = value;
+            entity.insert(style);
         },
-    };
+    }
 
-    let field = selector(&mut style);
+    Ok(())
+}
+
+fn entity_attribute(name: &str, value: BorrowedAttributeValue) -> Result<Option<Entity>, IntegrationError> {
+    use BorrowedAttributeValue as Val;
 
     match value {
-        Val::Any(value) => {
-            let value = value.as_any().downcast_ref::<Attr<T>>().unwrap();
-            *field = value.0.clone();
+        Val::Any(value) => value
+            .as_any()
+            .downcast_ref::<Attr<Entity>>()
+            .map(|value| Some(value.0))
+            .ok_or_else(|| IntegrationError::InvalidAttributeValue(name.to_owned())),
+        Val::None => Ok(None),
+        _ => Err(IntegrationError::InvalidAttributeValue(name.to_owned())),
+    }
+}
+
+fn set_proxied_entity(world: &mut World, proxy: Entity, value: BorrowedAttributeValue) -> Result<(), IntegrationError> {
+    let entity = entity_attribute("entity", value)?;
+
+    // Checked up front, add_child would panic on a despawned entity
+    if let Some(entity) = entity {
+        entity_mut(world, entity)?;
+    }
+
+    let previous = std::mem::replace(
+        &mut world
+            .get_mut::<EntityProxy>(proxy)
+            .ok_or_else(|| IntegrationError::UnsupportedAttribute { name: "entity".to_owned(), entity: proxy })?
+            .entity,
+        entity,
+    );
+
+    if let Some(mut previous) = previous.and_then(|previous| world.get_entity_mut(previous)) {
+        previous.remove_parent();
+    }
+
+    if let Some(entity) = entity {
+        entity_mut(world, proxy)?.add_child(entity);
+    }
+
+    Ok(())
+}
+
+fn set_portal_target(world: &mut World, portal: Entity, value: BorrowedAttributeValue) -> Result<(), IntegrationError> {
+    let target = entity_attribute("target", value)?;
+
+    if !world.get_entity(portal).is_some_and(|portal| portal.contains::<Portal>()) {
+        return Err(IntegrationError::UnsupportedAttribute { name: "target".to_owned(), entity: portal });
+    }
+
+    let content = children_parent(world, portal);
+
+    // Without a target, content is a top level UI node
+    match target {
+        Some(target) => {
+            entity_mut(world, content)?;
+            entity_mut(world, target)?.add_child(content);
         },
-        Val::None => {
-            *field = T::default();
+        None => {
+            entity_mut(world, content)?.remove_parent();
         },
-        _ => panic!("invalid attribute type"),
     }
-}
\ No newline at end of file
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use bevy::{prelude::{App, MinimalPlugins, World, Entity, Parent, Children, DespawnRecursiveExt, Events, Resource, NodeBundle, BuildWorldChildren, TextBundle, Res, Event, default}, ecs::system::Command, text::Text, ui::{Node, Style, Val}};
+    use dioxus::{prelude::{Scope, Element, Props, rsx, IntoDynNode, fc_to_builder}, core::ElementId};
+    use bevy_ui_dioxus_elements as dioxus_elements;
+    use bevy_ui_dioxus_elements::GlobalAttributes;
+
+    use crate::{plugin::DioxusPlugin, app_root::{DioxusRoot, DespawnDioxusRoot}, hooks::{use_resource, use_commands, use_world, use_system, use_event_reader}, integration::DioxusRoots, bevy_node::PortalContent, attributes::Attr, error::{DioxusError, IntegrationError}};
+
+    use super::IntegrationData;
+
+    #[derive(Resource, Clone)]
+    struct Count(u32);
+
+    #[derive(Resource, Clone)]
+    struct Items(Vec<u32>);
+
+    fn list_root(cx: Scope) -> Element {
+        let items = use_resource::<Items>(cx).0;
+        cx.render(rsx! {
+            div {
+                for item in items {
+                    div {
+                        key: "{item}",
+                        "{item}"
+                    }
+                }
+            }
+        })
+    }
+
+    fn element_id(app: &App, root: Entity, entity: Entity) -> ElementId {
+        integration_data(app, root).element_map.id(entity).unwrap()
+    }
+
+    fn test_app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, DioxusPlugin::default()));
+        app
+    }
+
+    fn mount(app: &mut App, root: fn(Scope) -> Element) -> Entity {
+        let root = app.world.spawn(DioxusRoot::new(root)).id();
+        app.update();
+        root
+    }
+
+    fn app_with_root(root: fn(Scope) -> Element) -> (App, Entity) {
+        let mut app = test_app();
+        let root = mount(&mut app, root);
+        (app, root)
+    }
+
+    fn parent(world: &World, entity: Entity) -> Entity {
+        world.get::<Parent>(entity).unwrap().get()
+    }
+
+    fn integration_data(app: &App, root: Entity) -> &IntegrationData {
+        app.world.non_send_resource::<DioxusRoots>().integration_data(root)
+    }
+
+    fn texts(world: &mut World) -> Vec<(Entity, String)> {
+        world
+            .query::<(Entity, &Text)>()
+            .iter(world)
+            .map(|(entity, text)| (entity, text.sections[0].value.clone()))
+            .collect()
+    }
+
+    fn find_text(world: &mut World, value: &str) -> Option<Entity> {
+        texts(world)
+            .into_iter()
+            .find(|(_, text)| text == value)
+            .map(|(entity, _)| entity)
+    }
+
+    #[test]
+    fn portal_content_is_despawned_with_root() {
+        fn root(cx: Scope) -> Element {
+            cx.render(rsx! {
+                div {
+                    portal {
+                        div { "in portal" }
+                    }
+                }
+            })
+        }
+
+        let (mut app, root) = app_with_root(root);
+
+        let text = find_text(&mut app.world, "in portal").unwrap();
+        let content = app.world.get::<Parent>(text).unwrap().get();
+        let content = app.world.get::<Parent>(content).unwrap().get();
+        assert!(app.world.get::<PortalContent>(content).is_some());
+
+        app.world.entity_mut(root).despawn_recursive();
+        app.update();
+
+        assert!(app.world.get_entity(text).is_none());
+        assert!(app.world.get_entity(content).is_none());
+        assert!(!app.world.non_send_resource::<DioxusRoots>().is_mounted(root));
+    }
+
+    #[test]
+    fn failed_batch_leaves_no_stray_nodes() {
+        fn root(cx: Scope) -> Element {
+            // width takes a Val, so applying it fails before the div is attached
+            cx.render(rsx! {
+                div { width: Attr(5.0f32) }
+            })
+        }
+
+        let (mut app, root) = app_with_root(root);
+
+        let errors: Vec<IntegrationError> = app.world
+            .resource_mut::<Events<DioxusError>>()
+            .drain()
+            .map(|error| error.error)
+            .collect();
+        assert_eq!(errors, vec![IntegrationError::InvalidAttributeValue("width".to_owned())]);
+
+        let nodes = app.world.query::<&Node>().iter(&app.world).count();
+        assert_eq!(nodes, 1);
+        assert_eq!(integration_data(&app, root).stack, vec![root]);
+    }
+
+    #[test]
+    fn dynamic_text_at_template_root() {
+        fn root(cx: Scope) -> Element {
+            let count = use_resource::<Count>(cx).0;
+            cx.render(rsx! { "{count}" })
+        }
+
+        let mut app = test_app();
+        app.insert_resource(Count(1));
+        let root = mount(&mut app, root);
+
+        let text = find_text(&mut app.world, "1").unwrap();
+        assert_eq!(parent(&app.world, text), root);
+        assert!(integration_data(&app, root).element_map.contains(text));
+
+        app.insert_resource(Count(2));
+        app.update();
+
+        assert_eq!(find_text(&mut app.world, "2"), Some(text));
+    }
+
+    #[test]
+    fn dynamic_text_nested_in_template() {
+        fn root(cx: Scope) -> Element {
+            let count = use_resource::<Count>(cx).0;
+            cx.render(rsx! {
+                div {
+                    "static"
+                    div { "Count: {count}" }
+                }
+            })
+        }
+
+        let mut app = test_app();
+        app.insert_resource(Count(1));
+        let root = mount(&mut app, root);
+
+        let text = find_text(&mut app.world, "Count: 1").unwrap();
+        let inner = parent(&app.world, text);
+        let outer = parent(&app.world, inner);
+        assert_eq!(parent(&app.world, outer), root);
+        assert_eq!(app.world.get::<Children>(outer).unwrap()[1], inner);
+        assert!(integration_data(&app, root).element_map.contains(text));
+
+        app.insert_resource(Count(2));
+        app.update();
+
+        assert_eq!(find_text(&mut app.world, "Count: 2"), Some(text));
+    }
+
+    #[test]
+    fn hydrating_non_text_node_replaces_it_in_place() {
+        let mut world = World::new();
+        let root = world.spawn(NodeBundle::default()).id();
+        let mut data = IntegrationData::default();
+        data.set_root(root);
+
+        let template = world.spawn(NodeBundle::default()).id();
+        let before = world.spawn(NodeBundle::default()).id();
+        let hydrated = world.spawn(NodeBundle::default()).id();
+        let after = world.spawn(NodeBundle::default()).id();
+        world.entity_mut(template).push_children(&[before, hydrated, after]);
+        data.stack.push(template);
+
+        data.hydrate_text(&mut world, &[1], "hydrated", ElementId(1)).unwrap();
+
+        let text = find_text(&mut world, "hydrated").unwrap();
+        assert!(world.get_entity(hydrated).is_none());
+        assert_eq!(world.get::<Children>(template).unwrap().to_vec(), vec![before, text, after]);
+        assert_eq!(data.element_map.get(ElementId(1)), Ok(text));
+    }
+
+    #[test]
+    fn hydrating_non_text_template_root_replaces_it_on_stack() {
+        let mut world = World::new();
+        let root = world.spawn(NodeBundle::default()).id();
+        let mut data = IntegrationData::default();
+        data.set_root(root);
+
+        let template = world.spawn(NodeBundle::default()).id();
+        data.stack.push(template);
+
+        data.hydrate_text(&mut world, &[], "hydrated", ElementId(1)).unwrap();
+
+        let text = find_text(&mut world, "hydrated").unwrap();
+        assert!(world.get_entity(template).is_none());
+        assert_eq!(data.stack, vec![root, text]);
+        assert_eq!(data.element_map.get(ElementId(1)), Ok(text));
+    }
+
+    #[test]
+    fn keyed_list_churn_keeps_map_and_world_in_sync() {
+        let mut app = test_app();
+        app.insert_resource(Items((0..10).collect()));
+        let root = mount(&mut app, list_root);
+
+        let count_nodes = |world: &mut World| world.query::<&Node>().iter(world).count();
+        let nodes = count_nodes(&mut app.world);
+        let others = app.world.entities().len() as usize - nodes;
+
+        let mut items: Vec<u32> = (0..10).collect();
+        let mut next = 10;
+        let mut seed = 7u32;
+
+        for _ in 0..200 {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            let at = (seed >> 16) as usize % (items.len() + 1);
+
+            match seed % 4 {
+                0 if items.len() < 20 => {
+                    items.insert(at, next);
+                    next += 1;
+                },
+                1 if !items.is_empty() => {
+                    items.remove(at.min(items.len() - 1));
+                },
+                2 => items.rotate_left(at),
+                _ => items.reverse(),
+            }
+
+            app.insert_resource(Items(items.clone()));
+            app.update();
+
+            // Root, list and a div with a text per item, nothing left behind
+            let nodes = count_nodes(&mut app.world);
+            assert_eq!(nodes, 2 + items.len() * 2);
+            assert_eq!(app.world.entities().len() as usize - nodes, others);
+
+            let texts: Vec<String> = items.iter().map(|item| item.to_string()).collect();
+            let list = app.world.get::<Children>(root).unwrap()[0];
+            let rendered: Vec<String> = app.world
+                .get::<Children>(list)
+                .map(|children| children.to_vec())
+                .unwrap_or_default()
+                .into_iter()
+                .map(|item| app.world.get::<Children>(item).unwrap()[0])
+                .map(|text| app.world.get::<Text>(text).unwrap().sections[0].value.clone())
+                .collect();
+            assert_eq!(rendered, texts);
+
+            // Every id either points to a live entity or is freed
+            let element_map = &integration_data(&app, root).element_map;
+            for index in 0..element_map.len() {
+                let id = ElementId(index);
+                match element_map.get(id) {
+                    Ok(entity) => assert!(app.world.get_entity(entity).is_some()),
+                    Err(error) => assert_eq!(error, IntegrationError::UnknownElement(id)),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn removed_ids_are_freed_and_reused() {
+        let mut app = test_app();
+        app.insert_resource(Items(vec![0, 1, 2]));
+        let root = mount(&mut app, list_root);
+
+        let removed = find_text(&mut app.world, "2").unwrap();
+        let removed_div = parent(&app.world, removed);
+        let id = element_id(&app, root, removed_div);
+
+        app.insert_resource(Items(vec![0, 1]));
+        app.update();
+
+        assert!(app.world.get_entity(removed_div).is_none());
+        assert_eq!(
+            integration_data(&app, root).element_map.get(id),
+            Err(IntegrationError::UnknownElement(id)),
+        );
+
+        app.insert_resource(Items(vec![0, 1, 3]));
+        app.update();
+
+        let added = find_text(&mut app.world, "3").unwrap();
+        let added_div = parent(&app.world, added);
+        assert_eq!(integration_data(&app, root).element_map.get(id), Ok(added_div));
+    }
+
+    #[test]
+    fn replaced_nodes_are_despawned_with_their_children() {
+        fn root(cx: Scope) -> Element {
+            let show = use_resource::<Count>(cx).0 == 0;
+            cx.render(rsx! {
+                div {
+                    if show {
+                        rsx! { div { div { "nested" } } }
+                    } else {
+                        rsx! { "replaced" }
+                    }
+                }
+            })
+        }
+
+        let mut app = test_app();
+        app.insert_resource(Count(0));
+        let root = mount(&mut app, root);
+
+        let nested = find_text(&mut app.world, "nested").unwrap();
+        let inner = parent(&app.world, nested);
+        let outer = parent(&app.world, inner);
+        let id = element_id(&app, root, outer);
+
+        app.insert_resource(Count(1));
+        app.update();
+
+        assert!(find_text(&mut app.world, "replaced").is_some());
+        for entity in [outer, inner, nested] {
+            assert!(app.world.get_entity(entity).is_none());
+        }
+        assert!(integration_data(&app, root).element_map.get(id).is_err());
+    }
+
+    #[test]
+    fn static_portal_styles_go_to_its_content() {
+        fn root(cx: Scope) -> Element {
+            cx.render(rsx! {
+                portal {
+                    width: "10px",
+                    div { "in portal" }
+                }
+            })
+        }
+
+        let (mut app, _) = app_with_root(root);
+
+        let text = find_text(&mut app.world, "in portal").unwrap();
+        let content = parent(&app.world, parent(&app.world, text));
+        assert!(app.world.get::<PortalContent>(content).is_some());
+        assert_eq!(app.world.get::<Style>(content).unwrap().width, Val::Px(10.0));
+    }
+
+    #[test]
+    fn portal_respawns_content_despawned_with_its_target() {
+        #[derive(Resource, Clone)]
+        struct Target(Entity);
+
+        fn root(cx: Scope) -> Element {
+            let target = use_resource::<Target>(cx).0;
+            cx.render(rsx! {
+                portal {
+                    target: Attr(target),
+                    div { "in portal" }
+                }
+            })
+        }
+
+        let mut app = test_app();
+        let overlay = app.world.spawn(NodeBundle::default()).id();
+        app.insert_resource(Target(overlay));
+        mount(&mut app, root);
+
+        let text = find_text(&mut app.world, "in portal").unwrap();
+        let content = parent(&app.world, parent(&app.world, text));
+        assert_eq!(parent(&app.world, content), overlay);
+
+        app.world.entity_mut(overlay).despawn_recursive();
+        let overlay = app.world.spawn(NodeBundle::default()).id();
+        app.insert_resource(Target(overlay));
+        app.update();
+
+        assert!(app.world.get_entity(content).is_none());
+        let children = app.world.get::<Children>(overlay).unwrap();
+        assert_eq!(children.len(), 1);
+        assert!(app.world.get::<PortalContent>(children[0]).is_some());
+    }
+
+    #[test]
+    fn invalid_static_attribute_is_skipped() {
+        fn root(cx: Scope) -> Element {
+            cx.render(rsx! {
+                div {
+                    width: "wide",
+                    height: "5px",
+                    "rendered"
+                }
+            })
+        }
+
+        let (mut app, _) = app_with_root(root);
+
+        let text = find_text(&mut app.world, "rendered").unwrap();
+        let style = app.world.get::<Style>(parent(&app.world, text)).unwrap();
+        assert_eq!(style.width, Val::Auto);
+        assert_eq!(style.height, Val::Px(5.0));
+    }
+
+    #[test]
+    fn despawning_root_keeps_placed_entity() {
+        #[derive(Resource, Clone)]
+        struct Placed(Entity);
+
+        fn root(cx: Scope) -> Element {
+            let placed = use_resource::<Placed>(cx).0;
+            cx.render(rsx! {
+                div {
+                    entity { entity: Attr(placed) }
+                }
+            })
+        }
+
+        let mut app = test_app();
+        let placed = app.world.spawn(TextBundle::from_section("placed", default())).id();
+        app.insert_resource(Placed(placed));
+        let root = mount(&mut app, root);
+
+        let proxy = parent(&app.world, placed);
+        assert_eq!(parent(&app.world, parent(&app.world, proxy)), root);
+
+        DespawnDioxusRoot { entity: root }.apply(&mut app.world);
+        app.update();
+
+        assert!(app.world.get_entity(root).is_none());
+        assert!(app.world.get_entity(proxy).is_none());
+        assert!(app.world.get::<Parent>(placed).is_none());
+    }
+
+    #[test]
+    fn despawning_root_from_its_own_commands_keeps_placed_entity() {
+        #[derive(Resource, Clone)]
+        struct Placed(Entity);
+
+        #[derive(Resource, Clone)]
+        struct Quit(Option<Entity>);
+
+        fn root(cx: Scope) -> Element {
+            let placed = use_resource::<Placed>(cx).0;
+            let commands = use_commands(cx);
+            if let Some(entity) = use_resource::<Quit>(cx).0 {
+                commands.add(DespawnDioxusRoot { entity });
+            }
+            cx.render(rsx! {
+                div {
+                    entity { entity: Attr(placed) }
+                }
+            })
+        }
+
+        let mut app = test_app();
+        let placed = app.world.spawn(TextBundle::from_section("placed", default())).id();
+        app.insert_resource(Placed(placed));
+        app.insert_resource(Quit(None));
+        let root = mount(&mut app, root);
+
+        let proxy = parent(&app.world, placed);
+
+        app.insert_resource(Quit(Some(root)));
+        app.update();
+
+        assert!(app.world.get_entity(root).is_none());
+        assert!(app.world.get_entity(proxy).is_none());
+        assert!(app.world.get::<Parent>(placed).is_none());
+        assert!(!app.world.non_send_resource::<DioxusRoots>().is_mounted(root));
+    }
+
+    #[test]
+    fn system_runs_with_world_from_render() {
+        fn root(cx: Scope) -> Element {
+            let system = use_system(cx, |count: Res<Count>| count.0 * 2);
+            let doubled = system.run(&mut use_world(cx).borrow_mut(), ());
+            cx.render(rsx! { "{doubled}" })
+        }
+
+        let mut app = test_app();
+        app.insert_resource(Count(2));
+        mount(&mut app, root);
+
+        assert!(find_text(&mut app.world, "4").is_some());
+    }
+
+    #[test]
+    fn clean_frames_do_not_render() {
+        static RENDERS: AtomicUsize = AtomicUsize::new(0);
+
+        #[allow(non_snake_case)]
+        fn Suspended(cx: Scope) -> Element {
+            cx.suspend().flatten()
+        }
+
+        fn root(cx: Scope) -> Element {
+            RENDERS.fetch_add(1, Ordering::Relaxed);
+            let count = use_resource::<Count>(cx).0;
+            cx.render(rsx! {
+                div { "{count}" }
+                Suspended {}
+            })
+        }
+
+        let mut app = test_app();
+        app.insert_resource(Count(1));
+        mount(&mut app, root);
+        assert_eq!(RENDERS.load(Ordering::Relaxed), 1);
+
+        let entities = app.world.entities().len();
+        app.update();
+        app.update();
+
+        assert_eq!(RENDERS.load(Ordering::Relaxed), 1);
+        assert_eq!(app.world.entities().len(), entities);
+
+        app.insert_resource(Count(2));
+        app.update();
+
+        assert_eq!(RENDERS.load(Ordering::Relaxed), 2);
+        assert!(find_text(&mut app.world, "2").is_some());
+    }
+
+    #[test]
+    fn roots_render_and_unmount_independently() {
+        fn hud(cx: Scope) -> Element {
+            cx.render(rsx! { div { "hud" } })
+        }
+
+        fn menu(cx: Scope) -> Element {
+            cx.render(rsx! { div { "menu" } })
+        }
+
+        let mut app = test_app();
+        let hud_root = mount(&mut app, hud);
+        let menu_root = mount(&mut app, menu);
+
+        let hud_text = find_text(&mut app.world, "hud").unwrap();
+        let menu_text = find_text(&mut app.world, "menu").unwrap();
+        assert_eq!(parent(&app.world, parent(&app.world, hud_text)), hud_root);
+        assert_eq!(parent(&app.world, parent(&app.world, menu_text)), menu_root);
+
+        app.world.entity_mut(hud_root).remove::<DioxusRoot>();
+        app.update();
+
+        assert!(app.world.get_entity(hud_text).is_none());
+        assert!(app.world.get::<Children>(hud_root).map_or(true, |children| children.is_empty()));
+        assert!(!app.world.non_send_resource::<DioxusRoots>().is_mounted(hud_root));
+        assert_eq!(find_text(&mut app.world, "menu"), Some(menu_text));
+        assert!(app.world.non_send_resource::<DioxusRoots>().is_mounted(menu_root));
+    }
+
+    #[test]
+    fn set_props_re_renders_root() {
+        #[derive(Props, PartialEq, Clone)]
+        struct Player {
+            id: u32,
+        }
+
+        fn root(cx: Scope<Player>) -> Element {
+            cx.render(rsx! { "player {cx.props.id}" })
+        }
+
+        let mut app = test_app();
+        let root = app.world.spawn(DioxusRoot::with_props(root, Player { id: 1 })).id();
+        app.update();
+
+        let text = find_text(&mut app.world, "player 1").unwrap();
+
+        let changed = app.world.get_mut::<DioxusRoot>(root).unwrap().set_props(Player { id: 2 });
+        assert!(changed);
+        app.update();
+
+        assert_eq!(find_text(&mut app.world, "player 2"), Some(text));
+
+        let changed = app.world.get_mut::<DioxusRoot>(root).unwrap().set_props(());
+        assert!(!changed);
+        app.update();
+
+        assert_eq!(find_text(&mut app.world, "player 2"), Some(text));
+    }
+
+    #[test]
+    fn mounting_onto_existing_panel_keeps_its_children() {
+        fn root(cx: Scope) -> Element {
+            cx.render(rsx! { div { "mounted" } })
+        }
+
+        let mut app = test_app();
+        let own = app.world.spawn(TextBundle::from_section("own", default())).id();
+        let panel = app.world.spawn(NodeBundle::default()).add_child(own).id();
+
+        app.world.entity_mut(panel).insert(DioxusRoot::new(root));
+        app.update();
+
+        let text = find_text(&mut app.world, "mounted").unwrap();
+        let mounted = parent(&app.world, text);
+        assert_eq!(app.world.get::<Children>(panel).unwrap().to_vec(), vec![own, mounted]);
+
+        app.world.entity_mut(panel).remove::<DioxusRoot>();
+        app.update();
+
+        assert!(app.world.get_entity(mounted).is_none());
+        assert_eq!(app.world.get::<Children>(panel).unwrap().to_vec(), vec![own]);
+    }
+
+    #[test]
+    fn reading_unregistered_events_is_empty() {
+        #[derive(Event, Clone)]
+        struct Unregistered;
+
+        fn root(cx: Scope) -> Element {
+            let received = use_event_reader::<Unregistered>(cx).len();
+            cx.render(rsx! { "received {received}" })
+        }
+
+        let (mut app, _) = app_with_root(root);
+
+        assert!(find_text(&mut app.world, "received 0").is_some());
+    }
+}
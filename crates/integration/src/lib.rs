@@ -0,0 +1,16 @@
+mod integration;
+mod template_map;
+mod element_map;
+mod integration_data;
+mod ui_node;
+mod bevy_node;
+mod static_attributes;
+
+pub mod plugin;
+pub mod app_root;
+pub mod attributes;
+pub mod hooks;
+pub mod subscriptions;
+pub mod commands;
+pub mod world_access;
+pub mod error;
\ No newline at end of file
@@ -1,23 +1,8 @@
-use app_root::AppRootComponent;
-use attributes::Attr;
-use bevy::{prelude::{App, Commands, Camera2dBundle, PreStartup, Component}, DefaultPlugins, ui::Val};
-
-mod plugin;
-mod integration;
-mod app_root;
-mod template_map;
-mod element_map;
-mod integration_data;
-mod ui_node;
-mod bevy_node;
-mod attributes;
-mod hooks;
-
-use dioxus::prelude::{Scope, Element, rsx, use_state, use_effect, to_owned};
-use hooks::{use_query, use_world};
-use plugin::DioxusPlugin;
+use bevy::{prelude::{App, Commands, Camera2dBundle, PreStartup, Component, Entity, TextBundle, default}, DefaultPlugins, ui::Val};
+use dioxus::prelude::{Scope, Element, rsx, use_state};
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
 
+use bevy_ui_dioxus::{plugin::DioxusPlugin, hooks::{use_query, use_world}, attributes::Attr};
 use bevy_ui_dioxus_elements::GlobalAttributes;
 use bevy_ui_dioxus_elements as dioxus_elements;
 
@@ -25,10 +10,9 @@ fn main() {
     App::new()
         .add_plugins((
             DefaultPlugins,
-            DioxusPlugin,
+            DioxusPlugin::new(app_root),
             WorldInspectorPlugin::new(),
         ))
-        .insert_resource(AppRootComponent(app_root))
         .add_systems(PreStartup, setup)
         .run();
 }
@@ -38,9 +22,13 @@ struct Count {
     value: usize,
 }
 
+#[derive(Component)]
+struct Label;
+
 fn setup(mut commands: Commands) {
     commands.spawn(Camera2dBundle::default());
     commands.spawn(Count { value: 5 });
+    commands.spawn((TextBundle::from_section("Spawned outside of Dioxus", default()), Label));
 }
 
 fn app_root(cx: Scope) -> Element {
@@ -49,12 +37,18 @@ fn app_root(cx: Scope) -> Element {
     let mut count = use_query::<&Count>(world);
     let count = count.single(&world.borrow()).value;
 
+    let mut label = use_query::<(Entity, &Label)>(world);
+    let label = label.single(&world.borrow()).0;
+
     let left = use_state(cx, || 50.0);
 
     cx.render(rsx! {
         div {
             left: Attr(Val::Px(*left.get())),
             "Counter: {count}"
+            entity {
+                entity: Attr(label),
+            }
         }
     })
 }
\ No newline at end of file
@@ -1,13 +1,90 @@
-use bevy::prelude::{Plugin, Update, Startup};
+use std::sync::Arc;
 
-use crate::integration::{update_dioxus, setup_dioxus};
+use bevy::{prelude::{Plugin, Update, Startup, World, IntoSystemConfigs, SystemSet, default}, ecs::schedule::{ScheduleLabel, BoxedScheduleLabel}, ui::Style};
+use dioxus::prelude::{Scope, Element, Properties};
 
-pub struct DioxusPlugin;
+use crate::{integration::{update_dioxus, setup_dioxus}, app_root::DioxusRoot, error::DioxusError};
+
+type RunCondition = Arc<dyn Fn(&World) -> bool + Send + Sync>;
+
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DioxusSet;
+
+// Root component is optional, DioxusRoot components can be spawned
+// at runtime with or without it. There's no target camera setting,
+// bevy_ui 0.11 draws every UI node to every camera and has no way to
+// tie a node to one
+pub struct DioxusPlugin {
+    root: Option<DioxusRoot>,
+    root_style: Style,
+    schedule: BoxedScheduleLabel,
+    run_condition: Option<RunCondition>,
+}
+
+impl Default for DioxusPlugin {
+    fn default() -> Self {
+        Self {
+            root: None,
+            root_style: default(),
+            schedule: Box::new(Update),
+            run_condition: None,
+        }
+    }
+}
+
+impl DioxusPlugin {
+    pub fn new(root: fn(Scope) -> Element) -> Self {
+        Self::with_props(root, ())
+    }
+    pub fn with_props<P: Properties + Clone + Send + Sync + 'static>(
+        root: fn(Scope<P>) -> Element,
+        props: P,
+    ) -> Self {
+        Self {
+            root: Some(DioxusRoot::with_props(root, props)),
+            ..default()
+        }
+    }
+    pub fn root_style(mut self, style: Style) -> Self {
+        self.root_style = style;
+        self
+    }
+    // update_dioxus runs in DioxusSet, configure the set for ordering
+    pub fn in_schedule(mut self, schedule: impl ScheduleLabel) -> Self {
+        self.schedule = Box::new(schedule);
+        self
+    }
+    // update_dioxus already skips rendering when nothing is dirty, the run
+    // condition is for apps that want to control when UI gets updated
+    pub fn run_if(mut self, condition: impl Fn(&World) -> bool + Send + Sync + 'static) -> Self {
+        self.run_condition = Some(Arc::new(condition));
+        self
+    }
+}
 
 impl Plugin for DioxusPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
-        app
-            .add_systems(Startup, setup_dioxus)
-            .add_systems(Update, update_dioxus);
+        app.add_event::<DioxusError>();
+
+        if let Some(root) = &self.root {
+            let root = root.clone();
+            let root_style = self.root_style.clone();
+
+            app.add_systems(Startup, move |world: &mut World| {
+                setup_dioxus(world, root.clone(), root_style.clone());
+            });
+        }
+
+        let update = update_dioxus.in_set(DioxusSet);
+
+        match &self.run_condition {
+            Some(condition) => {
+                let condition = condition.clone();
+                app.add_systems(self.schedule.clone(), update.run_if(move |world: &World| condition(world)));
+            },
+            None => {
+                app.add_systems(self.schedule.clone(), update);
+            },
+        }
     }
-}
\ No newline at end of file
+}
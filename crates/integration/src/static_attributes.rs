@@ -0,0 +1,193 @@
+use bevy::ui::{Style, Val, UiRect, Display, PositionType, Overflow, Direction, AlignItems, JustifyItems, AlignSelf, JustifySelf, AlignContent, JustifyContent, FlexDirection, FlexWrap, GridAutoFlow};
+
+use crate::error::IntegrationError;
+
+// Static attributes come from string literals in rsx, so they use CSS like
+// syntax: "10px", "50%", "auto", "flex-start", or "4px 8px" for rects
+pub fn apply_static_attribute(style: &mut Style, name: &str, value: &str) -> Result<(), IntegrationError> {
+    let invalid = || IntegrationError::InvalidStaticAttribute {
+        name: name.to_owned(),
+        value: value.to_owned(),
+    };
+
+    match name {
+        "display" => style.display = keyword(value, &[
+            ("flex", Display::Flex),
+            ("grid", Display::Grid),
+            ("none", Display::None),
+        ]).ok_or_else(invalid)?,
+        "position_type" => style.position_type = keyword(value, &[
+            ("relative", PositionType::Relative),
+            ("absolute", PositionType::Absolute),
+        ]).ok_or_else(invalid)?,
+        "overflow" => style.overflow = keyword(value, &[
+            ("visible", Overflow::visible()),
+            ("clip", Overflow::clip()),
+            ("clip_x", Overflow::clip_x()),
+            ("clip_y", Overflow::clip_y()),
+        ]).ok_or_else(invalid)?,
+        "direction" => style.direction = keyword(value, &[
+            ("inherit", Direction::Inherit),
+            ("ltr", Direction::LeftToRight),
+            ("rtl", Direction::RightToLeft),
+        ]).ok_or_else(invalid)?,
+        "left" => style.left = parse_val(value).ok_or_else(invalid)?,
+        "right" => style.right = parse_val(value).ok_or_else(invalid)?,
+        "top" => style.top = parse_val(value).ok_or_else(invalid)?,
+        "bottom" => style.bottom = parse_val(value).ok_or_else(invalid)?,
+        "width" => style.width = parse_val(value).ok_or_else(invalid)?,
+        "height" => style.height = parse_val(value).ok_or_else(invalid)?,
+        "min_width" => style.min_width = parse_val(value).ok_or_else(invalid)?,
+        "min_height" => style.min_height = parse_val(value).ok_or_else(invalid)?,
+        "max_width" => style.max_width = parse_val(value).ok_or_else(invalid)?,
+        "max_height" => style.max_height = parse_val(value).ok_or_else(invalid)?,
+        "aspect_ratio" => style.aspect_ratio = match value.trim() {
+            "none" => None,
+            value => Some(value.parse().map_err(|_| invalid())?),
+        },
+        "align_items" => style.align_items = keyword(value, &[
+            ("default", AlignItems::Default),
+            ("start", AlignItems::Start),
+            ("end", AlignItems::End),
+            ("flex_start", AlignItems::FlexStart),
+            ("flex_end", AlignItems::FlexEnd),
+            ("center", AlignItems::Center),
+            ("baseline", AlignItems::Baseline),
+            ("stretch", AlignItems::Stretch),
+        ]).ok_or_else(invalid)?,
+        "justify_items" => style.justify_items = keyword(value, &[
+            ("default", JustifyItems::Default),
+            ("start", JustifyItems::Start),
+            ("end", JustifyItems::End),
+            ("center", JustifyItems::Center),
+            ("baseline", JustifyItems::Baseline),
+            ("stretch", JustifyItems::Stretch),
+        ]).ok_or_else(invalid)?,
+        "align_self" => style.align_self = keyword(value, &[
+            ("auto", AlignSelf::Auto),
+            ("start", AlignSelf::Start),
+            ("end", AlignSelf::End),
+            ("flex_start", AlignSelf::FlexStart),
+            ("flex_end", AlignSelf::FlexEnd),
+            ("center", AlignSelf::Center),
+            ("baseline", AlignSelf::Baseline),
+            ("stretch", AlignSelf::Stretch),
+        ]).ok_or_else(invalid)?,
+        "justify_self" => style.justify_self = keyword(value, &[
+            ("auto", JustifySelf::Auto),
+            ("start", JustifySelf::Start),
+            ("end", JustifySelf::End),
+            ("center", JustifySelf::Center),
+            ("baseline", JustifySelf::Baseline),
+            ("stretch", JustifySelf::Stretch),
+        ]).ok_or_else(invalid)?,
+        "align_content" => style.align_content = keyword(value, &[
+            ("default", AlignContent::Default),
+            ("start", AlignContent::Start),
+            ("end", AlignContent::End),
+            ("flex_start", AlignContent::FlexStart),
+            ("flex_end", AlignContent::FlexEnd),
+            ("center", AlignContent::Center),
+            ("stretch", AlignContent::Stretch),
+            ("space_between", AlignContent::SpaceBetween),
+            ("space_evenly", AlignContent::SpaceEvenly),
+            ("space_around", AlignContent::SpaceAround),
+        ]).ok_or_else(invalid)?,
+        "justify_content" => style.justify_content = keyword(value, &[
+            ("default", JustifyContent::Default),
+            ("start", JustifyContent::Start),
+            ("end", JustifyContent::End),
+            ("flex_start", JustifyContent::FlexStart),
+            ("flex_end", JustifyContent::FlexEnd),
+            ("center", JustifyContent::Center),
+            ("space_between", JustifyContent::SpaceBetween),
+            ("space_evenly", JustifyContent::SpaceEvenly),
+            ("space_around", JustifyContent::SpaceAround),
+        ]).ok_or_else(invalid)?,
+        "margin" => style.margin = parse_rect(value).ok_or_else(invalid)?,
+        "padding" => style.padding = parse_rect(value).ok_or_else(invalid)?,
+        "border" => style.border = parse_rect(value).ok_or_else(invalid)?,
+        "flex_direction" => style.flex_direction = keyword(value, &[
+            ("row", FlexDirection::Row),
+            ("column", FlexDirection::Column),
+            ("row_reverse", FlexDirection::RowReverse),
+            ("column_reverse", FlexDirection::ColumnReverse),
+        ]).ok_or_else(invalid)?,
+        "flex_wrap" => style.flex_wrap = keyword(value, &[
+            ("no_wrap", FlexWrap::NoWrap),
+            ("wrap", FlexWrap::Wrap),
+            ("wrap_reverse", FlexWrap::WrapReverse),
+        ]).ok_or_else(invalid)?,
+        "flex_grow" => style.flex_grow = value.trim().parse().map_err(|_| invalid())?,
+        "flex_shrink" => style.flex_shrink = value.trim().parse().map_err(|_| invalid())?,
+        "flex_basis" => style.flex_basis = parse_val(value).ok_or_else(invalid)?,
+        "row_gap" => style.row_gap = parse_val(value).ok_or_else(invalid)?,
+        "column_gap" => style.column_gap = parse_val(value).ok_or_else(invalid)?,
+        "grid_auto_flow" => style.grid_auto_flow = keyword(value, &[
+            ("row", GridAutoFlow::Row),
+            ("column", GridAutoFlow::Column),
+            ("row_dense", GridAutoFlow::RowDense),
+            ("column_dense", GridAutoFlow::ColumnDense),
+        ]).ok_or_else(invalid)?,
+        // Grid templates and placements don't have a string syntax here yet,
+        // they can still be set dynamically with Attr
+        _ => return Err(IntegrationError::UnknownAttribute(name.to_owned())),
+    }
+
+    Ok(())
+}
+
+fn keyword<T: Clone>(value: &str, keywords: &[(&str, T)]) -> Option<T> {
+    // Both "flex-start" and "flex_start" work
+    let value = value.trim().replace('-', "_");
+
+    keywords
+        .iter()
+        .find(|(keyword, _)| *keyword == value)
+        .map(|(_, value)| value.clone())
+}
+
+type Unit = (&'static str, fn(f32) -> Val);
+
+fn parse_val(value: &str) -> Option<Val> {
+    let value = value.trim();
+    if value == "auto" {
+        return Some(Val::Auto);
+    }
+
+    let units: [Unit; 6] = [
+        ("px", Val::Px),
+        ("%", Val::Percent),
+        ("vw", Val::Vw),
+        ("vh", Val::Vh),
+        ("vmin", Val::VMin),
+        ("vmax", Val::VMax),
+    ];
+
+    for (unit, val) in units {
+        if let Some(number) = value.strip_suffix(unit) {
+            return number.trim().parse().ok().map(val);
+        }
+    }
+
+    // Unitless numbers are pixels
+    value.parse().ok().map(Val::Px)
+}
+
+fn parse_rect(value: &str) -> Option<UiRect> {
+    let vals = value
+        .split_whitespace()
+        .map(parse_val)
+        .collect::<Option<Vec<Val>>>()?;
+
+    // Same order as CSS shorthands
+    let rect = match vals[..] {
+        [all] => UiRect::all(all),
+        [vertical, horizontal] => UiRect::new(horizontal, horizontal, vertical, vertical),
+        [top, horizontal, bottom] => UiRect::new(horizontal, horizontal, top, bottom),
+        [top, right, bottom, left] => UiRect::new(left, right, top, bottom),
+        _ => return None,
+    };
+
+    Some(rect)
+}
\ No newline at end of file
@@ -0,0 +1,78 @@
+use std::{cell::RefCell, rc::Rc, sync::Arc};
+
+use bevy::{prelude::{World, Resource, Component, Entity}, utils::HashMap, ecs::component::Tick};
+use dioxus::prelude::ScopeState;
+
+pub type BevySubscriptions = Rc<RefCell<Subscriptions>>;
+
+type Check = Box<dyn FnMut(&World) -> bool>;
+type Update = Arc<dyn Fn() + Send + Sync>;
+
+// Checks are ran against the world every frame before rendering,
+// scopes of the ones that return true get marked as dirty
+#[derive(Default)]
+pub struct Subscriptions {
+    next_id: usize,
+    entries: HashMap<usize, (Check, Update)>,
+}
+
+impl Subscriptions {
+    pub fn subscribe(&mut self, check: Check, update: Update) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.entries.insert(id, (check, update));
+        id
+    }
+    pub fn unsubscribe(&mut self, id: usize) {
+        self.entries.remove(&id);
+    }
+    pub fn run(&mut self, world: &World) {
+        for (check, update) in self.entries.values_mut() {
+            if check(world) {
+                update();
+            }
+        }
+    }
+}
+
+// Unsubscribes when the hook holding it is dropped with its scope
+pub struct Subscription {
+    id: usize,
+    subscriptions: BevySubscriptions,
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        self.subscriptions.borrow_mut().unsubscribe(self.id);
+    }
+}
+
+pub fn subscribe(
+    cx: &ScopeState,
+    check: impl FnMut(&World) -> bool + 'static,
+) -> Subscription {
+    let subscriptions = cx.consume_context::<BevySubscriptions>().unwrap();
+    let id = subscriptions.borrow_mut().subscribe(Box::new(check), cx.schedule_update());
+
+    Subscription {
+        id,
+        subscriptions,
+    }
+}
+
+pub fn resource_change_tick<R: Resource>(world: &World) -> Option<Tick> {
+    // World doesn't expose Ref<R> for resources, so read ticks from storage
+    world
+        .components()
+        .resource_id::<R>()
+        .and_then(|id| world.storages().resources.get(id)?.get_ticks())
+        .map(|ticks| ticks.last_changed_tick())
+}
+
+pub fn component_change_tick<T: Component>(world: &World, entity: Entity) -> Option<Tick> {
+    // None when either the entity is despawned or the component is removed
+    world
+        .get_entity(entity)?
+        .get_change_ticks::<T>()
+        .map(|ticks| ticks.last_changed_tick())
+}
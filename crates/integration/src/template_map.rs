@@ -1,7 +1,7 @@
-use bevy::{utils::HashMap, text::{Text, TextStyle}};
-use dioxus::prelude::{TemplateNode, Template};
+use bevy::{utils::HashMap, text::{Text, TextStyle}, ui::Style, log::warn};
+use dioxus::prelude::{TemplateNode, Template, TemplateAttribute};
 
-use crate::{ui_node::{RootNode, NodeChildrenTree, NodeChild, Element, ChildNode}, bevy_node::{SimpleNode, ImageNode, ButtonNode, TextNode}};
+use crate::{ui_node::{RootNode, NodeChildrenTree, NodeChild, Element, ChildNode}, bevy_node::{SimpleNode, ImageNode, ButtonNode, TextNode, EntityNode, PortalNode}, error::IntegrationError, static_attributes::apply_static_attribute};
 
 #[derive(Default, Debug)]
 pub struct TemplateMap {
@@ -9,20 +9,28 @@ pub struct TemplateMap {
 }
 
 impl TemplateMap {
-    pub fn add(&mut self, template: Template) {
+    pub fn add(&mut self, template: Template) -> Result<(), IntegrationError> {
         let mut template_roots = Vec::with_capacity(template.roots.len());
         
         for node in template.roots {
-            let node = self.create_node(template.name.to_string(), node);
+            let node = self.create_node(template.name.to_string(), node)?;
             template_roots.push(node);
         }
 
         self.map.insert(template.name.to_string(), template_roots);
+        Ok(())
     }
-    fn create_node(&mut self, name: String, node: &TemplateNode) -> RootNode {
-        match node {
+    pub fn get(&self, name: &str, index: usize) -> Result<&RootNode, IntegrationError> {
+        self.map
+            .get(name)
+            .and_then(|roots| roots.get(index))
+            .ok_or_else(|| IntegrationError::UnknownTemplate { name: name.to_owned(), index })
+    }
+    fn create_node(&mut self, name: String, node: &TemplateNode) -> Result<RootNode, IntegrationError> {
+        let node = match node {
             TemplateNode::Element {
                 tag,
+                attrs,
                 children,
                 ..
             } => {
@@ -30,11 +38,11 @@ impl TemplateMap {
 
                 for node in *children {
                     let index = children_tree.placeholder();
-                    let child = self.create_child(&mut children_tree, name.clone(), node);
+                    let child = self.create_child(&mut children_tree, name.clone(), node)?;
                     children_tree.replace(index, child);
                 }
 
-                let element = Self::create_element(*tag);
+                let element = Self::create_element(tag, attrs)?;
                 
                 if children.is_empty() {
                     RootNode::Element { element }
@@ -48,49 +56,73 @@ impl TemplateMap {
             TemplateNode::Text { text } => RootNode::Text { node: Self::create_text(*text) },
             TemplateNode::DynamicText { .. } => RootNode::Text { node: Self::create_dynamic_text() },
             TemplateNode::Dynamic { .. } => RootNode::PlaceHolder,
-        }
+        };
+
+        Ok(node)
     }
     fn create_child(
         &mut self,
         children_tree: &mut NodeChildrenTree,
         name: String,
         node: &TemplateNode,
-    ) -> ChildNode {
-        match node {
+    ) -> Result<ChildNode, IntegrationError> {
+        let node = match node {
             TemplateNode::Element {
                 tag,
+                attrs,
                 children,
                 ..
             } => {
                 for node in *children {
                     children_tree.add(NodeChild::In);
                     let index = children_tree.placeholder();
-                    let child = self.create_child(children_tree, name.clone(), node);
+                    let child = self.create_child(children_tree, name.clone(), node)?;
                     children_tree.replace(index, child);
                     children_tree.add(NodeChild::Out);
                 }
 
-                ChildNode::Element { element: Self::create_element(*tag) }
+                ChildNode::Element { element: Self::create_element(tag, attrs)? }
             },
             TemplateNode::Text { text } => ChildNode::Text { node: Self::create_text(*text) },
             TemplateNode::DynamicText { .. } => ChildNode::Text { node: Self::create_dynamic_text() },
             TemplateNode::Dynamic { .. } => ChildNode::PlaceHolder,
-        }
+        };
+
+        Ok(node)
     }
-    fn create_element(tag: &str) -> Element {
+    fn create_element(tag: &str, attrs: &[TemplateAttribute]) -> Result<Element, IntegrationError> {
+        // Dynamic attributes are applied later through SetAttribute. A bad
+        // static one is only skipped, the rest of the template still works
+        let mut style = Style::default();
+        for attr in attrs {
+            if let TemplateAttribute::Static { name, value, .. } = attr {
+                if let Err(error) = apply_static_attribute(&mut style, name, value) {
+                    warn!("skipped static attribute in {tag}: {error}");
+                }
+            }
+        }
+
         // Wish I could avoid using string for tags
-        match tag {
+        let element = match tag {
             "div" => Element::Div {
-                node: SimpleNode { }
+                node: SimpleNode { style },
             },
             "img" => Element::Image {
-                node: ImageNode { },
+                node: ImageNode { style },
             },
             "button" => Element::Button {
-                node: ButtonNode { },
+                node: ButtonNode { style },
             },
-            _ => panic!("Invalid tag, this shouldn't happen"),
-        }
+            "entity" => Element::Entity {
+                node: EntityNode { style },
+            },
+            "portal" => Element::Portal {
+                node: PortalNode { style },
+            },
+            _ => return Err(IntegrationError::UnknownTag(tag.to_owned())),
+        };
+
+        Ok(element)
     }
     fn create_text(text: impl Into<String>) -> TextNode {
         TextNode {
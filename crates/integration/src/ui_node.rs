@@ -1,4 +1,4 @@
-use crate::bevy_node::{TextNode, ImageNode, ButtonNode, SimpleNode};
+use crate::bevy_node::{TextNode, ImageNode, ButtonNode, SimpleNode, EntityNode, PortalNode};
 
 
 #[derive(Debug, Clone)]
@@ -16,7 +16,9 @@ pub enum RootNode {
     PlaceHolder,
 }
 
+// Built once per template, so prebuilt styles don't need boxing
 #[derive(Debug, Clone)]
+#[allow(clippy::large_enum_variant)]
 pub enum ChildNode {
     Element {
         element: Element,
@@ -38,6 +40,12 @@ pub enum Element {
     Button {
         node: ButtonNode,
     },
+    Entity {
+        node: EntityNode,
+    },
+    Portal {
+        node: PortalNode,
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -65,6 +73,7 @@ impl Default for NodeChildrenTree {
 }
 
 #[derive(Debug, Clone)]
+#[allow(clippy::large_enum_variant)]
 pub enum NodeChild {
     Node(ChildNode),
     In,
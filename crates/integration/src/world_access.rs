@@ -0,0 +1,110 @@
+use std::{cell::{Cell, RefCell, Ref, RefMut}, rc::Rc, ptr::null_mut, ops::{Deref, DerefMut}, thread};
+
+use bevy::prelude::World;
+
+// Instead of moving the World into the vdom, the exclusive system binds a
+// pointer to it only while Dioxus is rendering or polling tasks.
+// Guards must not be held across .await, the world is given back to
+// Bevy between polls and a guard still alive by then is a panic
+#[derive(Clone, Default)]
+pub struct BevyWorld(Rc<WorldAccess>);
+
+struct WorldAccess {
+    world: Cell<*mut World>,
+    // Keeps track of the handed out guards, like RefCell does
+    borrows: RefCell<()>,
+}
+
+impl Default for WorldAccess {
+    fn default() -> Self {
+        Self {
+            world: Cell::new(null_mut()),
+            borrows: RefCell::new(()),
+        }
+    }
+}
+
+impl BevyWorld {
+    pub fn with_bound<R>(&self, world: &mut World, f: impl FnOnce() -> R) -> R {
+        assert!(self.0.world.get().is_null(), "world is already bound");
+        self.0.world.set(world);
+
+        // Unbinds even if f panics, and the caller can't hold on to it
+        let _binding = WorldBinding { access: &self.0 };
+        f()
+    }
+    pub fn borrow(&self) -> WorldRef<'_> {
+        let borrow = self.0.borrows.borrow();
+        let world = self.0.world.get();
+        assert!(!world.is_null(), "world is only accessible while Dioxus is rendering");
+
+        // SAFETY: the pointer is only set inside with_bound, which holds the
+        // world's &mut for its whole duration. Guards still alive when it
+        // returns panic there, before the world is used again
+        WorldRef {
+            _borrow: borrow,
+            world: unsafe { &*world },
+        }
+    }
+    pub fn borrow_mut(&self) -> WorldMut<'_> {
+        let borrow = self.0.borrows.borrow_mut();
+        let world = self.0.world.get();
+        assert!(!world.is_null(), "world is only accessible while Dioxus is rendering");
+
+        // SAFETY: Same as borrow, RefMut makes sure it's the only guard
+        WorldMut {
+            _borrow: borrow,
+            world: unsafe { &mut *world },
+        }
+    }
+}
+
+struct WorldBinding<'a> {
+    access: &'a WorldAccess,
+}
+
+impl Drop for WorldBinding<'_> {
+    fn drop(&mut self) {
+        // A guard outliving the binding would point to a world we no longer
+        // borrow, like one held across .await in a task
+        let no_guards = self.access.borrows.try_borrow_mut().is_ok();
+        self.access.world.set(null_mut());
+
+        // Panicking again while unwinding would abort
+        if !no_guards && !thread::panicking() {
+            panic!("world guards shouldn't be held across .await or outlive the render");
+        }
+    }
+}
+
+pub struct WorldRef<'a> {
+    _borrow: Ref<'a, ()>,
+    world: &'a World,
+}
+
+impl Deref for WorldRef<'_> {
+    type Target = World;
+
+    fn deref(&self) -> &World {
+        self.world
+    }
+}
+
+pub struct WorldMut<'a> {
+    _borrow: RefMut<'a, ()>,
+    world: &'a mut World,
+}
+
+impl Deref for WorldMut<'_> {
+    type Target = World;
+
+    fn deref(&self) -> &World {
+        self.world
+    }
+}
+
+impl DerefMut for WorldMut<'_> {
+    fn deref_mut(&mut self) -> &mut World {
+        self.world
+    }
+}